@@ -1,40 +1,90 @@
 use {
-    crate::r#struct,
+    proc_macro2::TokenStream as TokenStream2,
     quote::{ToTokens, quote},
     std::collections::VecDeque,
     syn::{
+        Data,
+        Expr,
         ExprArray,
+        ExprRange,
         Fields,
         Ident,
-        LitInt,
+        LitStr,
         Path,
+        RangeLimits,
         Token,
         Type,
         UnOp,
+        Variant,
         Visibility,
         meta::ParseNestedMeta,
+        parse::{Parse, ParseStream},
         spanned::Spanned,
     },
 };
 
 #[derive(Default, Debug)]
 pub(crate) struct Attributes {
-    pub(crate) get:           FieldsVisibility,
-    pub(crate) set:           FieldsVisibility,
+    pub(crate) get:           Option<FieldsVisibility>,
+    pub(crate) set:           Option<FieldsVisibility>,
     pub(crate) r#impl:        Vec<MethodOrFunction>,
+    /// Associated consts of `consts = [NAME, ..]`, set next to the static functions
+    pub(crate) consts:        Vec<Ident>,
     pub(crate) custom_fields: Option<Ident>,
     pub(crate) custom_impls:  Option<Ident>,
     pub(crate) on_set:        Option<Path>,
+    /// `fn(&Self, &[PathStep], old: &Value, new: &Value)` fired after every write. Only the
+    /// hooks of the root userdata run, not those of the nested types written through it
+    pub(crate) on_change:     Option<Path>,
+    /// `fn(&Self, &[PathStep], new: &Value) -> mlua::Result<()>` run before every write, an
+    /// error aborts it. Only the root's runs too
+    pub(crate) validate:      Option<Path>,
+    /// `FromLua` for plain tables goes through `serde` (requires the `serde` feature)
+    pub(crate) serde:         bool,
+    /// How the `impl = [name(..)?]` entries report their errors
+    pub(crate) errors:        ErrorMode,
+    /// Lua position of the first tuple field or sequence element, 1 when unset
+    pub(crate) index_base:    Option<usize>,
 }
 
 #[derive(Debug)]
 pub(crate) struct MethodOrFunction {
-    pub(crate) name:    String,
-    pub(crate) args:    Vec<String>,
-    pub(crate) is_mut:  bool,
+    pub(crate) name:     String,
+    pub(crate) args:     Vec<String>,
+    pub(crate) is_mut:   bool,
     // Unused for now
     // pub(crate) is_ref:  bool,
-    pub(crate) is_self: bool,
+    pub(crate) is_self:  bool,
+    /// `Some` when the entry ends with `?`, i.e. it returns a `Result`
+    pub(crate) on_error: Option<ErrorMode>,
+    /// `async name(..)`, registered with `add_async_method`/`add_async_function` (requires the
+    /// `async` feature)
+    pub(crate) is_async: bool,
+}
+
+/// An entry of `impl = [...]`, i.e. `name(&self, u8)` optionally prefixed by `async`
+struct ImplEntry {
+    is_async: bool,
+    expr:     Expr,
+}
+
+impl Parse for ImplEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            is_async: input.parse::<Option<Token![async]>>()?.is_some(),
+            expr:     input.parse()?,
+        })
+    }
+}
+
+/// What a fallible method or function does with an `Err(e)`
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorMode {
+    /// Raises `mlua::Error::external(e)`
+    #[default]
+    Raise,
+    /// Returns `nil, err`
+    Nil,
 }
 
 impl Attributes {
@@ -42,69 +92,104 @@ impl Attributes {
         if let Some(ident) = meta.path.get_ident() {
             match ident.to_string().as_str() {
                 "get" => {
-                    self.get = FieldsVisibility::parse(meta)?;
+                    self.get = Some(FieldsVisibility::parse(meta)?);
                     Ok(())
                 },
                 "set" => {
-                    self.set = FieldsVisibility::parse(meta)?;
+                    self.set = Some(FieldsVisibility::parse(meta)?);
                     Ok(())
                 },
                 "impl" => {
-                    let arr: ExprArray = meta.value()?.parse()?;
+                    let content;
+                    let value = meta.value()?;
+                    syn::bracketed!(content in value);
+                    let entries = content.parse_terminated(ImplEntry::parse, Token![,])?;
 
                     let mut vec_elements = vec![];
-                    for elem in arr.elems {
-                        if let syn::Expr::Call(fn_call) = elem {
-                            if let syn::Expr::Path(ident) = *fn_call.func {
-                                let mut args = fn_call
-                                    .args
-                                    .into_iter()
-                                    .filter_map(|exp| {
-                                        match exp {
-                                            syn::Expr::Path(ident) => {
-                                                Some(exprpath_to_string(&ident))
-                                            },
-                                            syn::Expr::Reference(ident) => {
-                                                Some(Ok(ident.into_token_stream().to_string()))
-                                            },
-                                            _ => None,
-                                        }
-                                    })
-                                    .collect::<syn::Result<VecDeque<_>>>()?;
-
-                                let first_arg = args.front();
-                                let first_arg = first_arg.map(std::string::String::as_str);
-                                let is_self = matches!(
-                                    &first_arg,
-                                    Some("& mut self" | "mut self" | "& self" | "self")
-                                );
-
-                                vec_elements.push(MethodOrFunction {
-                                    name: exprpath_to_string(&ident)?,
-                                    is_mut: matches!(&first_arg, Some("& mut self" | "mut self")),
-                                    // Unused for now
-                                    // is_ref: matches!(
-                                    //     &first_arg,
-                                    //     Some("& mut self") | Some("& self")
-                                    // ),
-                                    is_self,
-                                    args: {
-                                        if is_self {
-                                            args.pop_front();
-                                        }
-                                        args.into()
+                    for ImplEntry {
+                        is_async,
+                        expr: elem,
+                    } in entries
+                    {
+                        // `name(&self, u8)?` returns a `Result`
+                        let (elem, on_error) = match elem {
+                            syn::Expr::Try(try_expr) => (*try_expr.expr, Some(self.errors)),
+                            elem => (elem, None),
+                        };
+                        let syn::Expr::Call(fn_call) = elem else {
+                            return Err(syn::Error::new_spanned(
+                                elem,
+                                "Expected a function signature like `name(&self, u8)`",
+                            ));
+                        };
+                        let syn::Expr::Path(ident) = *fn_call.func else {
+                            return Err(syn::Error::new_spanned(
+                                fn_call.func,
+                                "Expected the name of a function or a method",
+                            ));
+                        };
+
+                        let mut args = fn_call
+                            .args
+                            .into_iter()
+                            .map(|exp| {
+                                match exp {
+                                    exp @ (syn::Expr::Path(_) | syn::Expr::Reference(_)) => {
+                                        Ok(exp.into_token_stream().to_string())
                                     },
-                                });
-                            }
-                        } else {
-                            return Err(meta.error("Expected an identifier"));
-                        }
+                                    exp => {
+                                        Err(syn::Error::new_spanned(
+                                            exp,
+                                            "Unsupported argument, expected `self`, `&self`, \
+                                             `&mut self` or a type path (use `Vec::<u8>` for \
+                                             generic types)",
+                                        ))
+                                    },
+                                }
+                            })
+                            .collect::<syn::Result<VecDeque<_>>>()?;
+
+                        let first_arg = args.front();
+                        let first_arg = first_arg.map(std::string::String::as_str);
+                        let is_self = matches!(
+                            &first_arg,
+                            Some("& mut self" | "mut self" | "& self" | "self")
+                        );
+
+                        vec_elements.push(MethodOrFunction {
+                            name: exprpath_to_string(&ident),
+                            is_mut: matches!(&first_arg, Some("& mut self" | "mut self")),
+                            // Unused for now
+                            // is_ref: matches!(
+                            //     &first_arg,
+                            //     Some("& mut self") | Some("& self")
+                            // ),
+                            is_self,
+                            args: {
+                                if is_self {
+                                    args.pop_front();
+                                }
+                                args.into()
+                            },
+                            on_error,
+                            is_async,
+                        });
                     }
 
                     self.r#impl = vec_elements;
 
                     Ok(())
                 },
+                "consts" => {
+                    let content;
+                    let value = meta.value()?;
+                    syn::bracketed!(content in value);
+                    self.consts = content
+                        .parse_terminated(Ident::parse, Token![,])?
+                        .into_iter()
+                        .collect();
+                    Ok(())
+                },
                 "custom_fields" => {
                     self.custom_fields = Some(meta.value()?.parse()?);
                     Ok(())
@@ -117,6 +202,43 @@ impl Attributes {
                     self.on_set = Some(meta.value()?.parse::<Path>()?);
                     Ok(())
                 },
+                "on_change" => {
+                    self.on_change = Some(meta.value()?.parse::<Path>()?);
+                    Ok(())
+                },
+                "validate" => {
+                    self.validate = Some(meta.value()?.parse::<Path>()?);
+                    Ok(())
+                },
+                "index_base" => {
+                    let base: syn::LitInt = meta.value()?.parse()?;
+                    self.index_base = Some(base.base10_parse()?);
+                    Ok(())
+                },
+                "serde" => {
+                    self.serde = true;
+                    Ok(())
+                },
+                "errors" => {
+                    let mode: Ident = meta.value()?.parse()?;
+                    self.errors = match mode.to_string().as_str() {
+                        "raise" => ErrorMode::Raise,
+                        "nil" => ErrorMode::Nil,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                mode,
+                                "Expected `errors = raise` or `errors = nil`",
+                            ));
+                        },
+                    };
+                    // `errors = ...` may come after `impl = [...]`
+                    for fun in &mut self.r#impl {
+                        if let Some(on_error) = &mut fun.on_error {
+                            *on_error = self.errors;
+                        }
+                    }
+                    Ok(())
+                },
                 _ => Err(meta.error(format!("Unexpected attribute name: {ident}"))),
             }
         } else {
@@ -180,10 +302,14 @@ impl FieldsVisibility {
 
             let mut vec_elements = vec![];
             for elem in arr.elems {
-                if let syn::Expr::Path(ident) = elem {
-                    vec_elements.push(exprpath_to_string(&ident)?);
-                } else {
-                    return Err(meta.error("Expected an identifier"));
+                match elem {
+                    syn::Expr::Path(ident) => vec_elements.push(exprpath_to_string(&ident)),
+                    // Tuple fields, e.g. `set = [0]`
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Int(index),
+                        ..
+                    }) => vec_elements.push(index.base10_digits().to_owned()),
+                    _ => return Err(meta.error("Expected an identifier")),
                 }
             }
 
@@ -191,125 +317,369 @@ impl FieldsVisibility {
         }
     }
 
-    pub(crate) fn fields_from_visibility(&self, fields: &Fields) -> syn::Result<Vec<MinimalField>> {
-        match fields {
-            Fields::Named(fields_named) => {
-                Ok(fields_named
-                    .named
-                    .iter()
-                    .filter(|field| {
-                        match self {
-                            FieldsVisibility::Pub => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is Pub"),
-                                    FieldsVisibility::Pub
-                                )
-                            },
-                            FieldsVisibility::PubCrate => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is PubCrate"),
-                                    FieldsVisibility::Pub | FieldsVisibility::PubCrate
-                                )
-                            },
-                            FieldsVisibility::PubSuper => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is PubSuper"),
-                                    FieldsVisibility::Pub |
-                                        FieldsVisibility::PubCrate |
-                                        FieldsVisibility::PubSuper
-                                )
-                            },
-                            FieldsVisibility::Custom(v) => {
-                                if let Some(ident) = field
-                                    .ident
-                                    .as_ref()
-                                    .expect("Is named => has ident")
-                                    .span()
-                                    .source_text()
-                                {
-                                    v.contains(&ident)
-                                } else {
-                                    false
-                                }
-                            },
-                            FieldsVisibility::All => true,
-                            FieldsVisibility::None => false,
-                        }
-                    })
-                    .map(|field| {
-                        MinimalField {
-                            ty:           field.ty.clone(),
-                            ident:        IdentOrInt::Ident(
-                                field.ident.clone().expect("Is named => has ident"),
-                            ),
-                            ident_string: field
-                                .ident
-                                .as_ref()
-                                .expect("Is named => has ident")
-                                .span()
-                                .source_text()
-                                .expect("Field name should not be empty"),
-                        }
-                    })
-                    .collect())
+    fn contains(&self, vis: &Visibility, ident_string: &str) -> syn::Result<bool> {
+        Ok(match self {
+            FieldsVisibility::Pub => {
+                matches!(vis.try_into()?, FieldsVisibility::Pub)
             },
-            Fields::Unnamed(fields_unnamed) => {
-                Ok(fields_unnamed
-                    .unnamed
-                    .iter()
-                    .enumerate()
-                    .filter(|(idx, field)| {
-                        match self {
-                            FieldsVisibility::Pub => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is Pub"),
-                                    FieldsVisibility::Pub
-                                )
-                            },
-                            FieldsVisibility::PubCrate => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is PubCrate"),
-                                    FieldsVisibility::Pub | FieldsVisibility::PubCrate
-                                )
-                            },
-                            FieldsVisibility::PubSuper => {
-                                matches!(
-                                    (&field.vis).try_into().expect("Is PubSuper"),
-                                    FieldsVisibility::Pub |
-                                        FieldsVisibility::PubCrate |
-                                        FieldsVisibility::PubSuper
-                                )
-                            },
-                            FieldsVisibility::Custom(v) => {
-                                let field = idx.to_string();
-                                v.contains(&field)
-                            },
-                            FieldsVisibility::All => true,
-                            FieldsVisibility::None => false,
-                        }
-                    })
-                    .map(|(idx, field)| {
-                        MinimalField {
-                            ident_string: idx.to_string(),
-                            ident:        IdentOrInt::Int(syn::LitInt::new(
+            FieldsVisibility::PubCrate => {
+                matches!(
+                    vis.try_into()?,
+                    FieldsVisibility::Pub | FieldsVisibility::PubCrate
+                )
+            },
+            FieldsVisibility::PubSuper => {
+                matches!(
+                    vis.try_into()?,
+                    FieldsVisibility::Pub | FieldsVisibility::PubCrate | FieldsVisibility::PubSuper
+                )
+            },
+            FieldsVisibility::Custom(v) => v.iter().any(|name| name == ident_string),
+            FieldsVisibility::All => true,
+            FieldsVisibility::None => false,
+        })
+    }
+
+    /// Fields of an enum variant that are exposed. Variant fields are as visible as the enum
+    /// itself, and `Custom` entries can either be `field` (any variant) or `Variant::field`.
+    /// `#[mlua(...)]` is refused on variant fields, so they have no constraints.
+    pub(crate) fn variant_fields_from_visibility(
+        &self,
+        enum_vis: &Visibility,
+        variant: &Variant,
+    ) -> syn::Result<Vec<MinimalField>> {
+        let mut minimal_fields = vec![];
+
+        for (idx, field) in variant.fields.iter().enumerate() {
+            let rust_name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => idx.to_string(),
+            };
+            let qualified_name = format!("{}::{rust_name}", variant.ident);
+
+            if self.contains(enum_vis, &rust_name)? || self.contains(enum_vis, &qualified_name)? {
+                minimal_fields.push(MinimalField {
+                    ident:        match &field.ident {
+                        Some(ident) => IdentOrInt::Ident(ident.clone()),
+                        None => {
+                            IdentOrInt::Int(syn::LitInt::new(
                                 &idx.to_string(),
                                 proc_macro2::Span::call_site(),
-                            )),
-                            ty:           field.ty.clone(),
+                            ))
+                        },
+                    },
+                    ident_string: rust_name,
+                    ty:           field.ty.clone(),
+                    index:        position_index(),
+                    constraints:  FieldConstraints::default(),
+                });
+            }
+        }
+
+        Ok(minimal_fields)
+    }
+
+    /// Fields that are exposed for `access`, given the struct-level visibility and the
+    /// field-level `#[mlua(...)]` attributes (which take precedence).
+    pub(crate) fn fields_from_visibility(
+        &self,
+        fields: &Fields,
+        access: FieldAccess,
+    ) -> syn::Result<Vec<MinimalField>> {
+        let mut minimal_fields = vec![];
+
+        for (idx, field) in fields.iter().enumerate() {
+            let field_attributes = FieldAttributes::parse(field)?;
+            let rust_name = match &field.ident {
+                Some(ident) => ident.to_string(),
+                None => idx.to_string(),
+            };
+
+            let is_exposed = match (access, &field_attributes) {
+                (_, FieldAttributes { skip: true, .. }) |
+                (
+                    FieldAccess::Get,
+                    FieldAttributes {
+                        writeonly: true, ..
+                    },
+                ) |
+                (FieldAccess::Set, FieldAttributes { readonly: true, .. }) => false,
+                (FieldAccess::Get, FieldAttributes { readonly: true, .. }) |
+                (
+                    FieldAccess::Set,
+                    FieldAttributes {
+                        writeonly: true, ..
+                    },
+                ) => true,
+                _ => self.contains(&field.vis, &rust_name)?,
+            };
+
+            if !is_exposed {
+                continue;
+            }
+
+            minimal_fields.push(MinimalField {
+                index:        field_attributes.index.unwrap_or_else(position_index),
+                constraints:  field_attributes.constraints,
+                ident_string: field_attributes.rename.unwrap_or(rust_name),
+                ident:        match &field.ident {
+                    Some(ident) => IdentOrInt::Ident(ident.clone()),
+                    None => {
+                        IdentOrInt::Int(syn::LitInt::new(
+                            &idx.to_string(),
+                            proc_macro2::Span::call_site(),
+                        ))
+                    },
+                },
+                ty:           field.ty.clone(),
+            });
+        }
+
+        Ok(minimal_fields)
+    }
+}
+
+/// Which side of a field [`FieldsVisibility::fields_from_visibility`] is looking at.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum FieldAccess {
+    Get,
+    Set,
+}
+
+/// Field-level helper attribute:
+///
+/// ```ignore
+/// #[mlua(rename = "hp", readonly)]
+/// health: u32,
+/// #[mlua(skip)]
+/// cache: Vec<u8>,
+/// #[mlua(index = (u32, u32))]
+/// grid: Grid,
+/// ```
+///
+/// `readonly` and `writeonly` expose the field even if it isn't in the struct-level `get`/`set`.
+/// `skip`ped fields are hidden from Lua and built with `Default::default()` by `FromLua`.
+/// `index` is the `Idx` of the `Index<Idx>` the field is proxied with, `usize` by default. Keys
+/// aren't bound checked: a panic of the `Index`, like on an unknown key, is raised as a Lua error.
+#[derive(Default, Debug)]
+pub(crate) struct FieldAttributes {
+    pub(crate) rename:      Option<String>,
+    pub(crate) skip:        bool,
+    pub(crate) readonly:    bool,
+    pub(crate) writeonly:   bool,
+    pub(crate) index:       Option<Type>,
+    pub(crate) constraints: FieldConstraints,
+}
+
+/// Constraints checked when Lua writes a field, and when `FromLua` or a builder builds the type:
+///
+/// ```ignore
+/// #[mlua(range = 0..=100)]
+/// hp: u8,
+/// #[mlua(clamp = 0.0..=1.0)]
+/// volume: f32,
+/// #[mlua(max_len = 32, validate = crate::check_name)]
+/// name: String,
+/// ```
+///
+/// `max_len` compares with `len()`: bytes for strings, like `#` in Lua, elements for
+/// collections. `validate` is a `fn(&T) -> Result<(), E>` with `E: Display`. `on_change` gets the
+/// value once clamped.
+#[derive(Clone, Default, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct FieldConstraints {
+    pub(crate) range:    Option<ExprRange>,
+    pub(crate) clamp:    Option<ExprRange>,
+    pub(crate) max_len:  Option<Expr>,
+    pub(crate) validate: Option<Path>,
+}
+
+impl FieldConstraints {
+    /// Checks `place`, a mutable place of the field, against the constraints, clamping it.
+    /// Errors name the field `owner` + `step`, `step` being a `&str` of it in Lua syntax: `.hp`,
+    /// `[1]`.
+    pub(crate) fn check(
+        &self,
+        owner: &str,
+        step: &TokenStream2,
+        place: &TokenStream2,
+    ) -> TokenStream2 {
+        let clamp = self.clamp.as_ref().map(|range| {
+            let (start, end) = (&range.start, &range.end);
+            quote! {
+                if #place < #start {
+                    #place = #start;
+                } else if #place > #end {
+                    #place = #end;
+                }
+            }
+        });
+        let range = self.range.as_ref().map(|range| {
+            let reason = format!("expected a value in {}", range_to_string(range));
+            quote! {
+                if !::std::ops::RangeBounds::contains(&(#range), &#place) {
+                    return Err(::mlua_gen::invalid_field(#owner, #step, #reason));
+                }
+            }
+        });
+        let max_len = self.max_len.as_ref().map(|max_len| {
+            quote! {
+                if #place.len() > #max_len {
+                    return Err(::mlua_gen::invalid_field(
+                        #owner,
+                        #step,
+                        format!("expected a length of at most {}", #max_len),
+                    ));
+                }
+            }
+        });
+        let validate = self.validate.as_ref().map(|path| {
+            quote! {
+                if let Err(err) = (#path)(&#place) {
+                    return Err(::mlua_gen::invalid_field(#owner, #step, err));
+                }
+            }
+        });
+
+        quote! {
+            #clamp
+            #range
+            #max_len
+            #validate
+        }
+    }
+
+    /// Assigns `value: ty` to `place` once it satisfies the constraints, see [`Self::check`].
+    pub(crate) fn write(
+        &self,
+        owner: &str,
+        step: &TokenStream2,
+        place: &TokenStream2,
+        ty: &Type,
+        value: &TokenStream2,
+    ) -> TokenStream2 {
+        if self == &Self::default() {
+            return quote!(#place = #value;);
+        }
+
+        let check = self.check(owner, step, &quote!(__mlua_gen_new));
+        let mutability = self.clamp.is_some().then(|| quote!(mut));
+
+        quote! {
+            let #mutability __mlua_gen_new: #ty = #value;
+            #check
+            #place = __mlua_gen_new;
+        }
+    }
+}
+
+fn range_to_string(range: &ExprRange) -> String {
+    let bound = |bound: &Option<Box<Expr>>| {
+        bound
+            .as_ref()
+            .map(|bound| bound.to_token_stream().to_string())
+            .unwrap_or_default()
+    };
+    let limits = match range.limits {
+        RangeLimits::HalfOpen(_) => "..",
+        RangeLimits::Closed(_) => "..=",
+    };
+    format!("{}{limits}{}", bound(&range.start), bound(&range.end))
+}
+
+impl FieldAttributes {
+    pub(crate) fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut field_attributes = Self::default();
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("mlua"))
+        {
+            attr.parse_nested_meta(|meta| {
+                let Some(ident) = meta.path.get_ident() else {
+                    return Err(meta.error("Expected an ident."));
+                };
+
+                match ident.to_string().as_str() {
+                    "rename" => {
+                        field_attributes.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    },
+                    "skip" => field_attributes.skip = true,
+                    "readonly" => field_attributes.readonly = true,
+                    "writeonly" => field_attributes.writeonly = true,
+                    "index" => field_attributes.index = Some(meta.value()?.parse()?),
+                    "range" => field_attributes.constraints.range = Some(meta.value()?.parse()?),
+                    "clamp" => {
+                        let range: ExprRange = meta.value()?.parse()?;
+                        if !matches!(
+                            (&range.start, &range.limits, &range.end),
+                            (Some(_), RangeLimits::Closed(_), Some(_))
+                        ) {
+                            return Err(syn::Error::new_spanned(
+                                range,
+                                "Expected an inclusive range like `0..=100`",
+                            ));
                         }
-                    })
-                    .collect())
-            },
-            Fields::Unit => Ok(Vec::new()),
+                        field_attributes.constraints.clamp = Some(range);
+                    },
+                    "max_len" => {
+                        field_attributes.constraints.max_len = Some(meta.value()?.parse()?);
+                    },
+                    "validate" => {
+                        field_attributes.constraints.validate = Some(meta.value()?.parse()?);
+                    },
+                    _ => return Err(meta.error(format!("Unexpected field attribute: {ident}"))),
+                }
+
+                Ok(())
+            })?;
+        }
+
+        if field_attributes.readonly && field_attributes.writeonly {
+            return Err(syn::Error::new(
+                field.span(),
+                "A field can't be both `readonly` and `writeonly`, use `skip` instead",
+            ));
+        }
+
+        if field.ident.is_none() && (field_attributes.rename.is_some() || field_attributes.skip) {
+            return Err(syn::Error::new(
+                field.span(),
+                "`rename` and `skip` are only supported on named fields",
+            ));
+        }
+
+        Ok(field_attributes)
+    }
+
+    /// Removes every `#[mlua(...)]` from the fields, since `rustc` doesn't know about them.
+    pub(crate) fn strip(data: &mut Data) {
+        let fields: Box<dyn Iterator<Item = &mut syn::Field>> = match data {
+            Data::Struct(ds) => Box::new(ds.fields.iter_mut()),
+            Data::Enum(de) => Box::new(de.variants.iter_mut().flat_map(|v| v.fields.iter_mut())),
+            Data::Union(_) => Box::new(std::iter::empty()),
+        };
+
+        for field in fields {
+            field.attrs.retain(|attr| !attr.path().is_ident("mlua"));
         }
     }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub struct MinimalField {
+    /// Name of the field on the Lua side (tuple index for unnamed fields).
     pub ident_string: String,
     pub ident:        IdentOrInt,
     pub ty:           Type,
+    /// `Idx` of the `Index<Idx>` the field is proxied with.
+    pub index:        Type,
+    pub constraints:  FieldConstraints,
+}
+
+/// Default `Idx` of a field: a 1-based position on the Lua side.
+pub(crate) fn position_index() -> Type {
+    syn::parse_quote!(usize)
 }
 
 #[derive(Debug, Hash, PartialEq, Eq)]
@@ -327,11 +697,14 @@ impl ToTokens for IdentOrInt {
     }
 }
 
-fn exprpath_to_string(exprpath: &syn::ExprPath) -> syn::Result<String> {
-    exprpath.path.span().source_text().ok_or_else(|| {
-        syn::Error::new(
-            exprpath.span(),
-            format!("Expected {exprpath:?} to have a valid `source_text`"),
-        )
-    })
+/// `Variant::field` from its segments: the source text of a multi-token path needs span joining,
+/// which is nightly-only.
+fn exprpath_to_string(exprpath: &syn::ExprPath) -> String {
+    exprpath
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
 }
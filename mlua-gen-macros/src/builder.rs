@@ -1,16 +1,48 @@
 use {
-    crate::attr::MethodOrFunction,
-    proc_macro2::TokenStream as TokenStream2,
+    crate::{
+        attr::{ErrorMode, FieldAttributes, MethodOrFunction},
+        project::{field_checks, field_from_lua},
+    },
+    proc_macro2::{Span, TokenStream as TokenStream2},
     quote::quote,
     std::borrow::Borrow,
-    syn::{Field, Fields, FieldsNamed, FieldsUnnamed, parse_str},
+    syn::{
+        Field,
+        Fields,
+        FieldsNamed,
+        FieldsUnnamed,
+        Ident,
+        Path,
+        parse_str,
+        punctuated::Punctuated,
+        token::Comma,
+    },
 };
 
+/// `to_globals`, `to_globals_as` and `to_table` of `LuaBuilder`, which all go through the
+/// `to_table_as` of the type.
+pub(crate) fn registration(name: &Ident) -> TokenStream2 {
+    quote! {
+        fn to_globals(lua: &::mlua::Lua) -> ::mlua::Result<()> {
+            Self::to_globals_as(lua, stringify!(#name))
+        }
+
+        fn to_globals_as<S: AsRef<str>>(lua: &::mlua::Lua, s: S) -> ::mlua::Result<()> {
+            Self::to_table_as(lua, &lua.globals(), s)
+        }
+
+        fn to_table(lua: &::mlua::Lua, target: &::mlua::Table) -> ::mlua::Result<()> {
+            Self::to_table_as(lua, target, stringify!(#name))
+        }
+    }
+}
+
 pub(crate) fn builder_for_functions(
     name: &TokenStream2,
     fns: Vec<&MethodOrFunction>,
+    consts: &[Ident],
 ) -> TokenStream2 {
-    if fns.is_empty() {
+    if fns.is_empty() && consts.is_empty() {
         quote!(Ok(None))
     } else {
         let public_functions = fns
@@ -30,12 +62,25 @@ pub(crate) fn builder_for_functions(
                     .unzip();
                 let fn_name = parse_str::<syn::Ident>(&fun.name)
                     .expect("This should always be a valid ident");
-                quote! {
-                    table.set(stringify!(#fn_name),
-                        lua.create_function(|this, (#(#args),*): (#(#tys),*)| {
-                            Ok(#name::#fn_name(#(#args),*))
-                        })?
-                    )?;
+                if fun.is_async {
+                    let result =
+                        call_result(&quote!(#name::#fn_name(#(#args),*).await), fun.on_error);
+                    quote! {
+                        table.set(stringify!(#fn_name),
+                            lua.create_async_function(|this, (#(#args),*): (#(#tys),*)| async move {
+                                #result
+                            })?
+                        )?;
+                    }
+                } else {
+                    let result = call_result(&quote!(#name::#fn_name(#(#args),*)), fun.on_error);
+                    quote! {
+                        table.set(stringify!(#fn_name),
+                            lua.create_function(|this, (#(#args),*): (#(#tys),*)| {
+                                #result
+                            })?
+                        )?;
+                    }
                 }
             })
             .collect::<Vec<_>>();
@@ -44,64 +89,313 @@ pub(crate) fn builder_for_functions(
             Ok({
                 let table = lua.create_table()?;
                 #(#public_functions)*
+                #( table.set(stringify!(#consts), #name::#consts)?; )*
                 Some(table)
             })
         )
     }
 }
 
+/// Wraps the call of an `impl = [...]` entry into the `mlua::Result` returned to mlua. `Err(e)` of
+/// fallible entries becomes `mlua::Error::external(e)`, either raised or returned as `nil, err`.
+fn call_result(call: &TokenStream2, on_error: Option<ErrorMode>) -> TokenStream2 {
+    match on_error {
+        None => quote!(Ok(#call)),
+        Some(ErrorMode::Raise) => quote!(#call.map_err(::mlua::Error::external)),
+        Some(ErrorMode::Nil) => quote!(Ok(#call.map_err(::mlua::Error::external))),
+    }
+}
+
+/// `__tostring` through `Display`, falling back to `Debug`. Types implementing neither keep mlua's
+/// default `Name: 0x...`. Also registers the operator metamethods (`__add`, `__eq`, ...) of the
+/// operator traits that are implemented.
+pub(crate) fn meta_methods() -> TokenStream2 {
+    quote! {
+        if let Some(display) = ::mlua_gen::probe!(Self => display) {
+            method_or_fns.add_meta_method(::mlua::MetaMethod::ToString, move |_, this, ()| {
+                Ok(display(this))
+            });
+        }
+        ::mlua_gen::add_operator_meta_methods::<Self, _>(method_or_fns, ::mlua_gen::operators!(Self));
+    }
+}
+
+/// `add_method`/`add_method_mut`/`add_function` calls registering every `impl = [...]` entry on
+/// the `UserData` (through the `method_or_fns` binding of `add_methods`).
+pub(crate) fn user_data_methods(impls: Vec<MethodOrFunction>) -> Vec<TokenStream2> {
+    impls
+        .into_iter()
+        .map(|method_or_fn| {
+            let method_or_fn_ident = syn::Ident::new(&method_or_fn.name, Span::call_site());
+            let method_or_fn_string = method_or_fn.name;
+
+            let owned_fields: Vec<_> = method_or_fn
+                .args
+                .iter()
+                .map(|arg| {
+                    Field {
+                        colon_token: None,
+                        attrs:       vec![],
+                        vis:         syn::Visibility::Inherited,
+                        mutability:  syn::FieldMutability::None,
+                        ident:       None,
+                        ty:          parse_str::<syn::Type>(arg).expect("Invalid type."),
+                    }
+                })
+                .collect();
+            let (argument, ty) = generate_tuple_access(owned_fields.iter());
+
+            let (method_or_fn_caller, this) = if method_or_fn.is_self {
+                (quote!(this.), quote!(this,))
+            } else {
+                (quote!(Self::), quote!())
+            };
+
+            // Async methods get an owned `UserDataRef`/`UserDataRefMut` and return a future
+            if method_or_fn.is_async {
+                let (add_kind, this) = match (method_or_fn.is_mut, method_or_fn.is_self) {
+                    (true, true) => (quote!(add_async_method_mut), quote!(mut this,)),
+                    (false, true) => (quote!(add_async_method), this),
+                    (_, false) => (quote!(add_async_function), this),
+                };
+                let result = call_result(
+                    &quote!(#method_or_fn_caller #method_or_fn_ident(#argument).await),
+                    method_or_fn.on_error,
+                );
+
+                return quote!(
+                    method_or_fns.#add_kind(#method_or_fn_string, |_, #this args: #ty| async move {
+                        #result
+                    });
+                );
+            }
+
+            let add_kind = match (method_or_fn.is_mut, method_or_fn.is_self) {
+                (true, true) => quote!(add_method_mut),
+                (false, true) => quote!(add_method),
+                (true, false) => quote!(add_function_mut),
+                (false, false) => quote!(add_function),
+            };
+
+            let result = call_result(
+                &quote!(#method_or_fn_caller #method_or_fn_ident(#argument)),
+                method_or_fn.on_error,
+            );
+
+            quote!(
+                method_or_fns.#add_kind(#method_or_fn_string, |_, #this args: #ty| {
+                    #result
+                });
+            )
+        })
+        .collect()
+}
+
+/// `validate` and `on_change` hooks of a root, run around every write to it
+#[derive(Clone, Copy)]
+pub(crate) struct SetHooks<'l> {
+    pub(crate) validate:  Option<&'l Path>,
+    pub(crate) on_change: Option<&'l Path>,
+}
+
+impl SetHooks<'_> {
+    pub(crate) const fn is_empty(self) -> bool {
+        self.validate.is_none() && self.on_change.is_none()
+    }
+
+    /// `MluaGenProject::root_resolver` of a type with these hooks and `on_set`
+    pub(crate) fn root_resolver(self, on_set: Option<&Path>) -> TokenStream2 {
+        let on_set = match on_set {
+            Some(path) => {
+                quote!(::std::option::Option::Some(
+                    ::std::sync::Arc::new(|| { (#path)(); }) as ::mlua_gen::OnSet
+                ))
+            },
+            None => quote!(::std::option::Option::None),
+        };
+        let hook_args = self.resolver_args();
+        quote! {
+            fn root_resolver(
+                lua: &::mlua::Lua,
+                root: ::mlua::AnyUserData,
+            ) -> ::mlua::Result<::mlua_gen::Resolver> {
+                ::mlua_gen::make_resolver::<Self>(lua, root, #on_set, #hook_args)
+            }
+        }
+    }
+
+    /// Last arguments of `::mlua_gen::make_resolver`
+    fn resolver_args(self) -> TokenStream2 {
+        let on_change = match self.on_change {
+            Some(path) => quote!(::std::option::Option::Some(#path as ::mlua_gen::OnChange<Self>)),
+            None => quote!(::std::option::Option::None),
+        };
+        let validate = match self.validate {
+            Some(path) => quote!(::std::option::Option::Some(#path as ::mlua_gen::Validate<Self>)),
+            None => quote!(::std::option::Option::None),
+        };
+        quote!(#on_change, #validate)
+    }
+
+    /// Surrounds `assign` with the hooks. Expects `lua`, `this: &mut Self` and the written
+    /// `new: mlua::Value` in scope, `current` being the place of type `ty` that `assign`
+    /// overwrites. `on_change` gets the stored value when `clamped`, `assign` changing it.
+    pub(crate) fn around(
+        self,
+        step: &TokenStream2,
+        current: &TokenStream2,
+        ty: &syn::Type,
+        assign: &TokenStream2,
+        clamped: bool,
+    ) -> TokenStream2 {
+        let validate = self
+            .validate
+            .map(|path| quote!((#path)(&*this, &[#step], &new)?;));
+        let (old, on_change) = match self.on_change {
+            Some(path) => {
+                let stored = clamped.then(
+                    || quote!(let new = ::mlua_gen::probe!(#ty => lua_copy)(#current, lua)?;),
+                );
+                (
+                    quote! {
+                        let old = ::mlua_gen::probe!(#ty => lua_copy)(#current, lua)
+                            .unwrap_or(::mlua::Value::Nil);
+                    },
+                    quote!(#stored (#path)(&*this, &[#step], &old, &new);),
+                )
+            },
+            None => (quote!(), quote!()),
+        };
+        quote!(#validate #old #assign #on_change)
+    }
+}
+
+/// `owner` is the Lua name of what is built, used by the errors of the field constraints.
 pub(crate) fn builder_for_fields(
     name: &TokenStream2,
+    owner: &str,
     fields: &Fields,
     is_function_wrap: bool,
+    serde: bool,
 ) -> TokenStream2 {
     match fields {
         Fields::Unit => quote! { Ok::<_, ::mlua::Error>(#name) },
-        Fields::Unnamed(unnamed) => builder_for_unnamed(name, unnamed, is_function_wrap),
-        Fields::Named(named) => builder_for_named(name, named, is_function_wrap),
+        Fields::Unnamed(unnamed) => builder_for_unnamed(name, owner, unnamed, is_function_wrap),
+        Fields::Named(named) => builder_for_named(name, owner, named, is_function_wrap, serde),
     }
 }
 
 fn builder_for_unnamed(
     name: &TokenStream2,
+    owner: &str,
     fields: &FieldsUnnamed,
     is_function_wrap: bool,
 ) -> TokenStream2 {
-    let (access, tys) = generate_tuple_access(fields.unnamed.iter());
-    let (first_arg, function_creation) = if is_function_wrap {
-        (quote!(_: ::mlua::Table), quote!(::mlua::Function::wrap))
-    } else {
-        (quote!(_), quote!(lua.create_function))
-    };
+    let args = builder_args(&quote!(args), &quote!(::mlua::MultiValue), is_function_wrap);
+    let values = fields.unnamed.iter().enumerate().map(|(zb, field)| {
+        let from_lua = field_from_lua(
+            &quote!(args.next().unwrap_or(::mlua::Value::Nil)),
+            &field.ty,
+            owner,
+            &zb.to_string(),
+        );
+        quote!(#from_lua?)
+    });
+    let constructor = checked_constructor(owner, &fields.unnamed, &quote!(#name (#(#values),*)));
 
     quote! {
-        #function_creation(|#first_arg, args: #tys| {
-            Ok(#name (#access))
+        lua.create_function(|lua, #args| {
+            let mut args = args.into_iter();
+            #constructor
         })
     }
 }
 
 fn builder_for_named(
     name: &TokenStream2,
+    owner: &str,
     fields: &FieldsNamed,
     is_function_wrap: bool,
+    serde: bool,
 ) -> TokenStream2 {
-    let names = fields.named.iter().map(|x| &x.ident);
-    let (first_arg, function_creation) = if is_function_wrap {
-        (quote!(_: ::mlua::Table), quote!(::mlua::Function::wrap))
-    } else {
-        (quote!(_), quote!(lua.create_function))
-    };
+    // The `FromLua` impl already goes through `serde`
+    if serde {
+        let args = builder_args(&quote!(data), name, is_function_wrap);
+        return quote! {
+            lua.create_function(|_, #args| Ok(data))
+        };
+    }
+
+    let args = builder_args(&quote!(data), &quote!(::mlua::Table), is_function_wrap);
+    let constructors = named_fields_from_table(owner, fields, &quote!(data));
+    let constructor =
+        checked_constructor(owner, &fields.named, &quote!(#name { #(#constructors),* }));
 
     quote! {
-        #function_creation(|#first_arg, data: ::mlua::Table| {
-            Ok(#name {
-                #( #names: data.get(stringify!(#names))?, )*
-            })
+        lua.create_function(|lua, #args| {
+            #constructor
         })
     }
 }
 
+/// `Ok(value)` once the constraints of its `fields` hold, see [`field_checks`].
+pub(crate) fn checked_constructor(
+    owner: &str,
+    fields: &Punctuated<Field, Comma>,
+    value: &TokenStream2,
+) -> TokenStream2 {
+    match field_checks(owner, fields, &quote!(this), false) {
+        Some(checks) => {
+            quote! {
+                let mut built = #value;
+                {
+                    let this = &mut built;
+                    #checks
+                }
+                Ok(built)
+            }
+        },
+        None => quote!(Ok(#value)),
+    }
+}
+
+/// `pattern: ty`, preceded by the table being called when the builder is its `__call`.
+fn builder_args(pattern: &TokenStream2, ty: &TokenStream2, is_function_wrap: bool) -> TokenStream2 {
+    if is_function_wrap {
+        quote!((_, #pattern): (::mlua::Table, #ty))
+    } else {
+        quote!(#pattern: #ty)
+    }
+}
+
+/// `field: probe!(ty => field_from_lua)(table.get("lua_name")?, lua)?` for every named field, honouring `#[mlua(rename)]`.
+/// `#[mlua(skip)]` fields are built with `Default::default()`. `owner` is the Lua name of what is
+/// built, see [`field_from_lua`].
+pub(crate) fn named_fields_from_table(
+    owner: &str,
+    fields: &FieldsNamed,
+    table: &TokenStream2,
+) -> Vec<TokenStream2> {
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("Is named");
+            // Errors are reported by `fields_from_visibility`
+            let field_attributes = FieldAttributes::parse(field).unwrap_or_default();
+
+            if field_attributes.skip {
+                quote!(#ident: ::std::default::Default::default())
+            } else {
+                let lua_name = field_attributes.rename.unwrap_or_else(|| ident.to_string());
+                let from_lua =
+                    field_from_lua(&quote!(#table.get(#lua_name)?), &field.ty, owner, &lua_name);
+                quote!(#ident: #from_lua?)
+            }
+        })
+        .collect()
+}
+
 /// Depending on if a tuple has 0, 1 or more elemnts, the syntax of a tuple is not the same.
 ///
 /// 0 => Nothing
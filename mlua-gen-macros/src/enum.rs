@@ -1,13 +1,30 @@
 use {
     crate::{
-        attr::MethodOrFunction,
-        builder::{builder_for_fields, builder_for_functions},
-        project::{field_get_body, field_set_body, proxy_dispatch_helpers},
+        attr::{FieldConstraints, MethodOrFunction, MinimalField, position_index},
+        builder::{
+            SetHooks,
+            builder_for_fields,
+            builder_for_functions,
+            meta_methods,
+            registration,
+            user_data_methods,
+        },
+        project::{
+            field_call_body,
+            field_from_lua,
+            field_get_body,
+            field_into_lua,
+            field_keys_body,
+            field_set_body,
+            field_tostring_body,
+            index_base_const,
+            index_base_expr,
+            proxy_index_dispatch,
+        },
         shared::remove_ty_from_generics,
     },
     proc_macro2::{Span, TokenStream as TokenStream2},
     quote::quote,
-    std::iter::repeat_with,
     syn::{DataEnum, Generics, Ident, Path, Variant},
 };
 
@@ -16,6 +33,7 @@ pub fn builder(
     name: &Ident,
     de: &DataEnum,
     functions: Vec<&MethodOrFunction>,
+    consts: &[Ident],
     generics: &Generics,
 ) -> TokenStream2 {
     let (names, builders): (Vec<_>, Vec<_>) = de
@@ -25,13 +43,20 @@ pub fn builder(
             let var_name = &v.ident;
             (
                 var_name,
-                builder_for_fields(&quote! {Self::#var_name}, &v.fields, false),
+                builder_for_fields(
+                    &quote! {Self::#var_name},
+                    &format!("{name}.{var_name}"),
+                    &v.fields,
+                    false,
+                    false,
+                ),
             )
         })
         .unzip();
     let no_ty_generics = remove_ty_from_generics(generics);
 
-    let builder_fn_code = builder_for_functions(&quote! {Self}, functions);
+    let builder_fn_code = builder_for_functions(&quote! {Self}, functions, consts);
+    let registration = registration(name);
 
     quote! {
         impl #generics ::mlua_gen::LuaBuilder<
@@ -50,30 +75,30 @@ pub fn builder(
                 #builder_fn_code
             }
 
-            fn to_globals(lua: &::mlua::Lua) -> ::mlua::Result<()> {
-                Self::to_globals_as(lua, stringify!(#name))
-            }
+            #registration
 
-            fn to_globals_as<S: AsRef<str>>(lua: &::mlua::Lua, s: S) -> ::mlua::Result<()> {
+            fn to_table_as<S: AsRef<str>>(
+                lua: &::mlua::Lua,
+                target: &::mlua::Table,
+                s: S,
+            ) -> ::mlua::Result<()> {
+                let (target, key) = ::mlua_gen::nested_table(lua, target, s.as_ref())?;
                 let table = Self::lua_builder(&lua)?;
 
                 if let Some(table_to_extend_with) = Self::lua_fn_builder(&lua)? {
                     // Equivalent to extend, tho, doesn't seems to exists
                     for pairs in table_to_extend_with.pairs() {
-                        let (k,v): (String, ::mlua::Function) = pairs?;
+                        let (k, v): (String, ::mlua::Value) = pairs?;
 
                         table.set(k, v)?;
                     }
                 }
 
-                lua.globals()
-                    .set(s.as_ref(), Self::lua_builder(&lua)?)?;
+                target.set(key, table)?;
 
                 Ok(())
             }
         }
-
-        impl #generics ::mlua_gen::AutomaticImplWhenMluaGen for #name #no_ty_generics { }
     }
 }
 
@@ -86,47 +111,65 @@ enum VariantKind {
 /// Per-variant codegen pieces used to build the `UserData` and
 /// `MluaGenProject` impls in one pass.
 struct VariantPieces {
-    kind:            VariantKind,
-    accessor:        String,
-    fields_arm:      TokenStream2,
-    project_get_arm: TokenStream2,
-    project_set_arm: TokenStream2,
-    build_proxy_arm: TokenStream2,
-    from_lua_match:  TokenStream2,
+    kind:                 VariantKind,
+    accessor:             String,
+    fields_arm:           TokenStream2,
+    project_get_arm:      TokenStream2,
+    project_set_arm:      TokenStream2,
+    project_tostring_arm: TokenStream2,
+    project_keys_arm:     TokenStream2,
+    project_call_arm:     TokenStream2,
+    build_proxy_arm:      TokenStream2,
+    from_lua_match:       TokenStream2,
+}
+
+/// A variant with the fields that `get` and `set` expose on it.
+pub(crate) struct ExposedVariant<'l> {
+    pub(crate) variant: &'l Variant,
+    pub(crate) get:     Vec<MinimalField>,
+    pub(crate) set:     Vec<MinimalField>,
 }
 
-pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
+pub(crate) fn user_data(
     name: &Ident,
     generics: &Generics,
-    variants: I,
+    variants: &[ExposedVariant],
     custom_field: Option<syn::Ident>,
+    impls: Vec<MethodOrFunction>,
     custom_method_or_fn: Option<syn::Ident>,
     on_set: Option<Path>,
+    hooks: SetHooks,
+    serde: bool,
+    index_base: Option<usize>,
 ) -> proc_macro2::TokenStream {
     let on_set_call = match &on_set {
         Some(path) => quote!( (#path)(); ),
         None => quote!(),
     };
-    let on_set_resolver = match &on_set {
-        Some(path) => {
-            quote! {
-                ::std::option::Option::Some(::std::sync::Arc::new(|| { (#path)(); })
-                    as ::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>)
-            }
-        },
-        None => quote!(::std::option::Option::None),
-    };
+    let root_resolver = hooks.root_resolver(on_set.as_ref());
 
     let pieces: Vec<VariantPieces> = variants
-        .map(|variant| build_variant_pieces(variant, &on_set_call, &on_set_resolver))
+        .iter()
+        .map(|variant| build_variant_pieces(name, variant, &on_set_call, hooks))
         .collect();
 
     let accessors: Vec<&String> = pieces.iter().map(|p| &p.accessor).collect();
     let fields_arms = pieces.iter().map(|p| &p.fields_arm);
     let project_get_arms = pieces.iter().map(|p| &p.project_get_arm);
     let project_set_arms = pieces.iter().map(|p| &p.project_set_arm);
+    let project_tostring_arms = pieces.iter().map(|p| &p.project_tostring_arm);
+    let project_keys = impl_project_keys(name, variants, &pieces);
+    let project_call = impl_project_call(name, &pieces);
     let build_proxy_arms = pieces.iter().map(|p| &p.build_proxy_arm);
     let from_lua_match = pieces.iter().map(|p| &p.from_lua_match);
+    let from_table = if serde {
+        quote!(::mlua_gen::from_table_serde!(lua, table))
+    } else {
+        quote! {
+            #(#from_lua_match)*
+            Err(::mlua::Error::runtime("No valid variant found."))
+        }
+    };
 
     let router_get_arms = pieces.iter().map(|p| {
         let s = &p.accessor;
@@ -163,9 +206,7 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
             #s => {
                 let mut p = path_s.clone();
                 p.push(::mlua_gen::PathStep::Variant(#s));
-                (ctx_s.set)(lua, &p, value)?;
-                ctx_s.fire_on_set();
-                Ok(())
+                ctx_s.set_and_fire(lua, &p, value)
             }
         }
     });
@@ -176,27 +217,32 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
         quote!()
     };
 
+    let method_or_fns = user_data_methods(impls);
+    let meta_methods = meta_methods();
+    let meta_pairs = meta_pairs();
     let extra_impls = if let Some(method_or_fn) = custom_method_or_fn {
-        quote!(#method_or_fn(methods))
+        quote!(#method_or_fn(method_or_fns))
     } else {
         quote!()
     };
 
     let non_typed_generics = remove_ty_from_generics(generics);
 
-    let proxy_helpers = proxy_dispatch_helpers();
+    let index_base = index_base_const(index_base);
 
     quote! {
         impl #generics ::mlua_gen::MluaGenProject for #name #non_typed_generics {
+            #index_base
+
+            #root_resolver
+
             fn project_get(
                 &self,
                 lua: &::mlua::Lua,
                 steps: &[::mlua_gen::PathStep],
             ) -> ::mlua::Result<::mlua::Value> {
                 let Some((step, rest)) = steps.split_first() else {
-                    return Err(::mlua::Error::runtime(
-                        "empty path: project_get on root must go through resolve_get",
-                    ));
+                    return ::mlua_gen::probe!(Self => lua_copy)(self, lua);
                 };
                 match step {
                     ::mlua_gen::PathStep::Variant(__variant) => match *__variant {
@@ -226,14 +272,33 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
                 }
             }
 
+            fn project_tostring(
+                &self,
+                lua: &::mlua::Lua,
+                steps: &[::mlua_gen::PathStep],
+            ) -> ::mlua::Result<::std::string::String> {
+                let Some((step, rest)) = steps.split_first() else {
+                    return Ok(::mlua_gen::lua_tostring(self, ::mlua_gen::probe!(Self => display)));
+                };
+                match step {
+                    ::mlua_gen::PathStep::Variant(__variant) => match *__variant {
+                        #(#project_tostring_arms)*
+                        _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                    },
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                }
+            }
+
+            #project_keys
+
+            #project_call
+
             fn build_proxy(
                 lua: &::mlua::Lua,
                 ctx: ::mlua_gen::Resolver,
                 path: ::std::vec::Vec<::mlua_gen::PathStep>,
                 vis: ::mlua_gen::Visibility,
             ) -> ::mlua::Result<::mlua::Table> {
-                #proxy_helpers
-
                 // Path ending in `Variant(name)` → per-variant proxy.
                 // Otherwise → router proxy whose `__index(name)` extends the path.
                 if let Some(::mlua_gen::PathStep::Variant(__variant)) = path.last().cloned() {
@@ -246,6 +311,11 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
                 } else {
                     let table = lua.create_table()?;
                     let mt = lua.create_table()?;
+                    ::mlua_gen::set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+                    ::mlua_gen::set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
+                    ::mlua_gen::set_proxy_operators::<Self>(
+                        lua, &mt, ctx.clone(), path.clone(), ::mlua_gen::operators!(Self),
+                    )?;
 
                     let ctx_g = ctx.clone();
                     let path_g = path.clone();
@@ -297,8 +367,7 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
             fn from_lua(value: ::mlua::Value, lua: &::mlua::Lua) -> ::mlua::Result<#name #non_typed_generics> {
                 match value {
                     ::mlua::Value::Table(table) => {
-                        #(#from_lua_match)*
-                        Err(::mlua::Error::runtime("No valid variant found."))
+                        #from_table
                     },
                     ::mlua::Value::UserData(user_data) => {
                         user_data.take()
@@ -315,21 +384,286 @@ pub(crate) fn user_data<'l, I: Iterator<Item = &'l Variant>>(
                 #extra_fields
             }
 
-            fn add_methods<MluaUserDataMethods: ::mlua::UserDataMethods<Self>>(methods: &mut MluaUserDataMethods) {
+            fn add_methods<MluaUserDataMethods: ::mlua::UserDataMethods<Self>>(method_or_fns: &mut MluaUserDataMethods) {
+                #(#method_or_fns)*
+                #meta_methods
+                #meta_pairs
                 #extra_impls
             }
         }
     }
 }
 
+/// `pairs` on the enum userdata iterates its active variant.
+fn meta_pairs() -> TokenStream2 {
+    quote! {
+        let pairs = |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
+            let keys = ::mlua_gen::with_parent::<Self, _>(&this, |this| {
+                <Self as ::mlua_gen::MluaGenProject>::project_keys(this, lua, &[])
+            })?;
+            let iter = ::mlua_gen::pairs_iterator::<::mlua::AnyUserData>(lua, keys)?;
+            Ok((iter, this, ::mlua::Value::Nil))
+        };
+        for metamethod in ::mlua_gen::PAIRS_METAMETHODS {
+            method_or_fns.add_meta_function(*metamethod, pairs);
+        }
+    }
+}
+
+/// `MluaGenProject::project_keys`. The enum itself iterates as its active variant.
+fn impl_project_keys(
+    name: &Ident,
+    variants: &[ExposedVariant],
+    pieces: &[VariantPieces],
+) -> TokenStream2 {
+    let active_keys_arms = variants.iter().zip(pieces).map(|(variant, p)| {
+        let ident = &variant.variant.ident;
+        let accessor = &p.accessor;
+        quote!(Self::#ident { .. } => Ok(::mlua_gen::ProxyKeys::Fields(&[#accessor])),)
+    });
+    let project_keys_arms = pieces.iter().map(|p| &p.project_keys_arm);
+
+    quote! {
+        fn project_keys(
+            &self,
+            lua: &::mlua::Lua,
+            steps: &[::mlua_gen::PathStep],
+        ) -> ::mlua::Result<::mlua_gen::ProxyKeys> {
+            let Some((step, rest)) = steps.split_first() else {
+                return match self {
+                    #(#active_keys_arms)*
+                };
+            };
+            match step {
+                ::mlua_gen::PathStep::Variant(__variant) => match *__variant {
+                    #(#project_keys_arms)*
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                },
+                _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+            }
+        }
+    }
+}
+
+/// `MluaGenProject::project_call`. Collections live in variant fields, so the
+/// path always goes through a variant.
+fn impl_project_call(name: &Ident, pieces: &[VariantPieces]) -> TokenStream2 {
+    let project_call_arms = pieces.iter().map(|p| &p.project_call_arm);
+
+    quote! {
+        fn project_call(
+            &mut self,
+            lua: &::mlua::Lua,
+            steps: &[::mlua_gen::PathStep],
+            __mlua_gen_method: ::mlua_gen::CollectionMethod,
+            __mlua_gen_args: ::mlua::MultiValue,
+        ) -> ::mlua::Result<::mlua::Value> {
+            match steps.split_first() {
+                Some((::mlua_gen::PathStep::Variant(__variant), rest)) => match *__variant {
+                    #(#project_call_arms)*
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                },
+                _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+            }
+        }
+    }
+}
+
+/// Walker a [`variant_walk_arm`] is generated for. `owner` is the Lua name of the variant, see
+/// [`field_call_body`].
+#[derive(Clone, Copy)]
+enum VariantWalk<'a> {
+    Keys,
+    Call { owner: &'a str },
+}
+
+impl VariantWalk<'_> {
+    fn field_body(self, access: &TokenStream2, ty: &syn::Type, name: &str) -> TokenStream2 {
+        match self {
+            Self::Keys => field_keys_body(access, ty, &position_index(), name),
+            Self::Call { owner } => field_call_body(access, ty, &position_index(), name, owner),
+        }
+    }
+
+    /// What the walker returns when the path stops at the variant.
+    fn at_variant(self, accessor: &str, keys: &TokenStream2) -> TokenStream2 {
+        match self {
+            Self::Keys => quote!(Ok(#keys)),
+            Self::Call { .. } => quote!(Err(::mlua_gen::bad_step(#accessor))),
+        }
+    }
+}
+
+/// [`variant_walk_arm`] of a named variant, walking the fields `idents`.
+fn named_variant_walk_arm(
+    accessor: &str,
+    variant_ident: &Ident,
+    idents: &[&Ident],
+    strings: &[&String],
+    field_tys: &[&syn::Type],
+    field_strings: &[String],
+    walk: VariantWalk<'_>,
+) -> TokenStream2 {
+    let field_arms: Vec<TokenStream2> = idents
+        .iter()
+        .zip(strings)
+        .map(|(id, s)| {
+            let position = field_strings
+                .iter()
+                .position(|field| field == *s)
+                .expect("exposed field of the variant");
+            let body = walk.field_body(&quote!((*#id)), field_tys[position], s);
+            quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
+        })
+        .collect();
+    variant_walk_arm(
+        accessor,
+        &quote!(Self::#variant_ident { #(#idents,)* .. }),
+        &walk.at_variant(
+            accessor,
+            &quote!(::mlua_gen::ProxyKeys::Fields(&[#(#strings),*])),
+        ),
+        &field_arms,
+    )
+}
+
+/// [`variant_walk_arm`] of a tuple variant, walking the positions `positions`.
+fn unnamed_variant_walk_arm(
+    accessor: &str,
+    variant_ident: &Ident,
+    pattern: &[TokenStream2],
+    field_idents: &[Ident],
+    field_tys: &[&syn::Type],
+    positions: &[usize],
+    walk: VariantWalk<'_>,
+) -> TokenStream2 {
+    let field_arms: Vec<TokenStream2> = positions
+        .iter()
+        .map(|zb| {
+            let id = &field_idents[*zb];
+            let body = walk.field_body(&quote!((*#id)), field_tys[*zb], &zb.to_string());
+            quote! { ::mlua_gen::PathStep::Tuple(#zb) => { #body } }
+        })
+        .collect();
+    let base = index_base_expr();
+    variant_walk_arm(
+        accessor,
+        &quote!(Self::#variant_ident( #(#pattern),* )),
+        &walk.at_variant(
+            accessor,
+            &quote! {
+                ::mlua_gen::ProxyKeys::Keys(::std::vec![
+                    #(::mlua::IntoLua::into_lua(#positions + #base, lua)?),*
+                ])
+            },
+        ),
+        &field_arms,
+    )
+}
+
+/// `project_keys`/`project_call` arm of a variant: `at_variant` when the path stops at the
+/// variant, else the field arms.
+fn variant_walk_arm(
+    accessor: &str,
+    pattern: &TokenStream2,
+    at_variant: &TokenStream2,
+    field_arms: &[TokenStream2],
+) -> TokenStream2 {
+    quote! {
+        #accessor => {
+            let #pattern = self else {
+                return Err(::mlua::Error::runtime(
+                    "variant changed under proxy",
+                ));
+            };
+            let Some((step, rest)) = rest.split_first() else {
+                return #at_variant;
+            };
+            match step {
+                #(#field_arms)*
+                _ => Err(::mlua_gen::bad_step(#accessor)),
+            }
+        },
+    }
+}
+
+/// Setter replacing the whole variant through `assign`, which reads the fields from `table`
+fn variant_setter_closure(
+    hooks: SetHooks,
+    accessor: &str,
+    assign: &TokenStream2,
+    on_set_call: &TokenStream2,
+) -> TokenStream2 {
+    if hooks.is_empty() {
+        return quote! {
+            |lua, this, table: ::mlua::Table| {
+                #assign
+                #on_set_call
+                Ok(())
+            }
+        };
+    }
+
+    let write = hooks.around(
+        &quote!(::mlua_gen::PathStep::Variant(#accessor)),
+        &quote!(&*this),
+        &syn::parse_quote!(Self),
+        assign,
+        false,
+    );
+    quote! {
+        |lua, this, table: ::mlua::Table| {
+            let new = ::mlua::Value::Table(table.clone());
+            #write
+            #on_set_call
+            Ok(())
+        }
+    }
+}
+
+/// `probe!(ty => field_from_lua)(table.get(key)?, lua)?`, a variant field read from the
+/// table given for the variant. `name` is the field, in the variant `owner`.
+fn field_from_table<K: quote::ToTokens>(
+    owner: &str,
+    ty: &syn::Type,
+    name: &str,
+    key: K,
+) -> TokenStream2 {
+    let from_lua = field_from_lua(&quote!(table.get(#key)?), ty, owner, name);
+    quote!(#from_lua?)
+}
+
 fn build_variant_pieces(
-    variant: &Variant,
+    name: &Ident,
+    ExposedVariant { variant, get, set }: &ExposedVariant,
     on_set_call: &TokenStream2,
-    on_set_resolver: &TokenStream2,
+    hooks: SetHooks,
 ) -> VariantPieces {
     let original_variant_string = variant.ident.to_string();
     let variant_ident = syn::Ident::new(&original_variant_string, Span::call_site());
     let accessor = original_variant_string.to_lowercase();
+    let is_get = |s: &String| get.iter().any(|f| &f.ident_string == s);
+    let is_set = |s: &String| set.iter().any(|f| &f.ident_string == s);
+    let owner = format!("{name}.{accessor}");
+    // Variant fields take no `#[mlua(...)]`, so no constraints either
+    let set_body = |id: &syn::Ident, ty: &syn::Type, s: &String| {
+        field_set_body(
+            &quote!((*#id)),
+            ty,
+            &position_index(),
+            s,
+            &owner,
+            &FieldConstraints::default(),
+        )
+    };
+    // Replacing the whole variant writes every field, so it needs all of them to be settable.
+    let variant_settable = variant.fields.iter().enumerate().all(|(idx, f)| {
+        is_set(
+            &f.ident
+                .as_ref()
+                .map_or_else(|| idx.to_string(), ToString::to_string),
+        )
+    });
 
     match &variant.fields {
         syn::Fields::Named(field_named) => {
@@ -340,11 +674,33 @@ fn build_variant_pieces(
                 .collect();
             let field_tys: Vec<&syn::Type> = field_named.named.iter().map(|f| &f.ty).collect();
             let field_strings: Vec<String> = field_idents.iter().map(|i| i.to_string()).collect();
+            let get_fields = field_idents
+                .iter()
+                .zip(field_tys.iter())
+                .zip(field_strings.iter())
+                .filter(|(_, s)| is_get(s));
+            let set_fields = field_idents
+                .iter()
+                .zip(field_tys.iter())
+                .zip(field_strings.iter())
+                .filter(|(_, s)| is_set(s));
+            let get_idents: Vec<&syn::Ident> = get_fields.clone().map(|((id, _), _)| *id).collect();
+            let get_strings: Vec<&String> = get_fields.clone().map(|(_, s)| s).collect();
+            let get_copies: Vec<TokenStream2> = get_fields
+                .clone()
+                .map(|((id, ty), _)| field_into_lua(&quote!(#id.to_owned()), ty))
+                .collect();
+            let set_idents: Vec<&syn::Ident> = set_fields.clone().map(|((id, _), _)| *id).collect();
+            let set_strings: Vec<&String> = set_fields.clone().map(|(_, s)| s).collect();
 
             let field_constructors = field_idents
                 .iter()
+                .zip(field_tys.iter())
                 .zip(field_strings.iter())
-                .map(|(id, s)| quote!(#id: table.get(#s)?,));
+                .map(|((id, ty), s)| {
+                    let value = field_from_table(&owner, ty, s, s);
+                    quote!(#id: #value,)
+                });
             let from_lua_match = quote! {
                 if let Ok(table_value) = table.get::<::mlua::Value>(#accessor) {
                     if let ::mlua::Value::Table(table) = table_value {
@@ -355,8 +711,23 @@ fn build_variant_pieces(
 
             let setter_constructors = field_idents
                 .iter()
+                .zip(field_tys.iter())
                 .zip(field_strings.iter())
-                .map(|(id, s)| quote!(#id: table.get(#s)?,));
+                .map(|((id, ty), s)| {
+                    let value = field_from_table(&owner, ty, s, s);
+                    quote!(#id: #value,)
+                });
+            let variant_setter = variant_settable.then(|| {
+                let setter = variant_setter_closure(
+                    hooks,
+                    &accessor,
+                    &quote!(*this = Self::#variant_ident { #(#setter_constructors)* };),
+                    on_set_call,
+                );
+                quote! {
+                    reserved_fields.add_field_method_set(#accessor, #setter);
+                }
+            });
             let fields_arm = quote! {
                 reserved_fields.add_field_function_get(
                     #accessor,
@@ -367,8 +738,7 @@ fn build_variant_pieces(
                         if !active {
                             return Ok(::mlua::Value::Nil);
                         }
-                        let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                        let ctx = ::mlua_gen::make_resolver::<Self>(this, on_set);
+                        let ctx = <Self as ::mlua_gen::MluaGenProject>::root_resolver(lua, this)?;
                         let path = ::std::vec![::mlua_gen::PathStep::Variant(#accessor)];
                         let table = <Self as ::mlua_gen::MluaGenProject>::build_proxy(
                             lua, ctx, path, ::mlua_gen::Visibility::Both,
@@ -376,36 +746,21 @@ fn build_variant_pieces(
                         Ok(::mlua::Value::Table(table))
                     },
                 );
-                reserved_fields.add_field_method_set(
-                    #accessor,
-                    |_, this, table: ::mlua::Table| {
-                        *this = Self::#variant_ident { #(#setter_constructors)* };
-                        #on_set_call
-                        Ok(())
-                    },
-                );
+                #variant_setter
             };
 
-            let project_get_field_arms = field_idents
-                .iter()
-                .zip(field_tys.iter())
-                .zip(field_strings.iter())
-                .map(|((id, ty), s)| {
-                    let body = field_get_body(&quote!((*#id)), ty, s);
-                    quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
-                });
-            let project_set_field_arms = field_idents
-                .iter()
-                .zip(field_tys.iter())
-                .zip(field_strings.iter())
-                .map(|((id, ty), s)| {
-                    let body = field_set_body(&quote!((*#id)), ty, s);
-                    quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
-                });
+            let project_get_field_arms = get_fields.clone().map(|((id, ty), s)| {
+                let body = field_get_body(&quote!((*#id)), ty, &position_index(), s);
+                quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
+            });
+            let project_set_field_arms = set_fields.clone().map(|((id, ty), s)| {
+                let body = set_body(id, ty, s);
+                quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
+            });
 
             let project_get_arm = quote! {
                 #accessor => {
-                    let Self::#variant_ident { #(#field_idents),* } = self else {
+                    let Self::#variant_ident { #(#get_idents,)* .. } = self else {
                         // Bare `enum.variant` on inactive → nil; deeper path → error.
                         if rest.is_empty() {
                             return Ok(::mlua::Value::Nil);
@@ -416,7 +771,7 @@ fn build_variant_pieces(
                     };
                     let Some((step, rest)) = rest.split_first() else {
                         let table = lua.create_table()?;
-                        #( table.set(#field_strings, #field_idents.to_owned())?; )*
+                        #( table.set(#get_strings, #get_copies?)?; )*
                         return ::mlua::IntoLua::into_lua(table, lua);
                     };
                     match step {
@@ -428,17 +783,28 @@ fn build_variant_pieces(
 
             let setter_constructors2 = field_idents
                 .iter()
+                .zip(field_tys.iter())
                 .zip(field_strings.iter())
-                .map(|(id, s)| quote!(#id: table.get(#s)?,));
+                .map(|((id, ty), s)| {
+                    let value = field_from_table(&owner, ty, s, s);
+                    quote!(#id: #value,)
+                });
+            let replace_variant = if variant_settable {
+                quote! {
+                    let table: ::mlua::Table =
+                        ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
+                    *self = Self::#variant_ident { #(#setter_constructors2)* };
+                    return Ok(());
+                }
+            } else {
+                not_settable(&accessor)
+            };
             let project_set_arm = quote! {
                 #accessor => {
                     if rest.is_empty() {
-                        let table: ::mlua::Table =
-                            ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                        *self = Self::#variant_ident { #(#setter_constructors2)* };
-                        return Ok(());
+                        #replace_variant
                     }
-                    let Self::#variant_ident { #(#field_idents),* } = self else {
+                    let Self::#variant_ident { #(#set_idents,)* .. } = self else {
                         return Err(::mlua::Error::runtime(
                             "variant changed under proxy",
                         ));
@@ -451,36 +817,73 @@ fn build_variant_pieces(
                 },
             };
 
-            let proxy_index_arms = field_idents
-                .iter()
-                .zip(field_tys.iter())
-                .zip(field_strings.iter())
-                .map(|((_id, ty), s)| {
-                    quote! {
-                        #s => {
-                            let mut p = path_g.clone();
-                            p.push(::mlua_gen::PathStep::Field(#s));
-                            proxy_index_dispatch::<#ty>(lua, ctx_g.clone(), p)
-                        }
+            let project_tostring_field_arms = get_fields.clone().map(|((id, ty), s)| {
+                let body = field_tostring_body(&quote!((*#id)), ty, &position_index(), s);
+                quote! { ::mlua_gen::PathStep::Field(#s) => { #body } }
+            });
+            let project_tostring_arm = quote! {
+                #accessor => {
+                    // The variant proxy itself prints as the whole enum.
+                    if rest.is_empty() {
+                        return Ok(::mlua_gen::lua_tostring(self, ::mlua_gen::probe!(Self => display)));
                     }
-                });
-            let proxy_newindex_arms = field_idents
-                .iter()
-                .zip(field_tys.iter())
-                .zip(field_strings.iter())
-                .map(|((_id, ty), s)| {
-                    quote! {
-                        #s => {
-                            let mut p = path_s.clone();
-                            p.push(::mlua_gen::PathStep::Field(#s));
-                            proxy_newindex_dispatch::<#ty>(lua, ctx_s.clone(), p, value)
-                        }
+                    let Self::#variant_ident { #(#get_idents,)* .. } = self else {
+                        return Err(::mlua::Error::runtime(
+                            "variant changed under proxy",
+                        ));
+                    };
+                    let (step, rest) = rest.split_first().expect("checked above");
+                    match step {
+                        #(#project_tostring_field_arms)*
+                        _ => Err(::mlua_gen::bad_step(#accessor)),
                     }
-                });
+                },
+            };
+
+            let project_keys_arm = named_variant_walk_arm(
+                &accessor,
+                &variant_ident,
+                &get_idents,
+                &get_strings,
+                &field_tys,
+                &field_strings,
+                VariantWalk::Keys,
+            );
+            let project_call_arm = named_variant_walk_arm(
+                &accessor,
+                &variant_ident,
+                &set_idents,
+                &set_strings,
+                &field_tys,
+                &field_strings,
+                VariantWalk::Call { owner: &owner },
+            );
+
+            let proxy_index_arms = get_fields.map(|((_id, ty), s)| {
+                let dispatch = proxy_index_dispatch(ty, &position_index());
+                quote! {
+                    #s => {
+                        let mut p = path_g.clone();
+                        p.push(::mlua_gen::PathStep::Field(#s));
+                        #dispatch
+                    }
+                }
+            });
+            let proxy_newindex_arms = set_fields.map(|(_, s)| {
+                quote! {
+                    #s => {
+                        let mut p = path_s.clone();
+                        p.push(::mlua_gen::PathStep::Field(#s));
+                        ctx_s.set_and_fire(lua, &p, value)
+                    }
+                }
+            });
             let build_proxy_arm = quote! {
                 #accessor => {
                     let table = lua.create_table()?;
                     let mt = lua.create_table()?;
+                    ::mlua_gen::set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+                    ::mlua_gen::set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
 
                     let ctx_g = ctx.clone();
                     let path_g = path.clone();
@@ -527,6 +930,9 @@ fn build_variant_pieces(
                 fields_arm,
                 project_get_arm,
                 project_set_arm,
+                project_tostring_arm,
+                project_keys_arm,
+                project_call_arm,
                 build_proxy_arm,
                 from_lua_match,
             }
@@ -539,29 +945,54 @@ fn build_variant_pieces(
                 .map(|i| syn::Ident::new(&format!("v{i}"), Span::call_site()))
                 .collect();
             let zero_based: Vec<usize> = (0..arity).collect();
-            let lua_indices: Vec<usize> = (1..=arity).collect();
             let zero_based_strs: Vec<String> = (0..arity).map(|i| i.to_string()).collect();
+            // Bind only the exposed positions, `_` elsewhere.
+            let pattern_for = |exposed: &dyn Fn(&String) -> bool| -> Vec<TokenStream2> {
+                field_idents
+                    .iter()
+                    .zip(zero_based_strs.iter())
+                    .map(|(id, s)| if exposed(s) { quote!(#id) } else { quote!(_) })
+                    .collect()
+            };
+            let get_pattern = pattern_for(&is_get);
+            let set_pattern = pattern_for(&is_set);
+            let get_positions: Vec<usize> = zero_based
+                .iter()
+                .copied()
+                .filter(|zb| is_get(&zb.to_string()))
+                .collect();
+            let base = index_base_expr();
+            let get_lua_indices = get_positions.iter().map(|zb| quote!(#zb + #base));
+            let get_copies = get_positions.iter().map(|zb| {
+                let id = &field_idents[*zb];
+                field_into_lua(&quote!(#id.to_owned()), field_tys[*zb])
+            });
 
-            let impl_from_lua = repeat_with(|| {
-                quote!(::mlua::FromLua::from_lua(
-                    sequence_value.next().ok_or_else(|| {
-                        ::mlua::Error::runtime("Not enough values in sequence table.")
-                    })??,
-                    lua,
-                )?)
-            })
-            .take(arity);
+            // Tables of tuple fields are keyed from `index_base`, like the snapshots and `pairs`
+            let fields_from_table: Vec<TokenStream2> = field_tys
+                .iter()
+                .zip(zero_based.iter())
+                .map(|(ty, zb)| field_from_table(&owner, ty, &zb.to_string(), quote!(#zb + #base)))
+                .collect();
             let from_lua_match = quote! {
                 if let Ok(table_value) = table.get::<::mlua::Value>(#accessor) {
                     if let ::mlua::Value::Table(table) = table_value {
-                        let mut sequence_value: ::mlua::TableSequence<::mlua::Value> =
-                            table.sequence_values();
-                        return Ok(Self::#variant_ident( #(#impl_from_lua),* ));
+                        return Ok(Self::#variant_ident( #(#fields_from_table),* ));
                     }
                 }
             };
 
-            let setter_indexed = (1..=arity).map(|i| quote!(table.get(#i)?));
+            let variant_setter = variant_settable.then(|| {
+                let setter = variant_setter_closure(
+                    hooks,
+                    &accessor,
+                    &quote!(*this = Self::#variant_ident( #(#fields_from_table),* );),
+                    on_set_call,
+                );
+                quote! {
+                    reserved_fields.add_field_method_set(#accessor, #setter);
+                }
+            });
             let fields_arm = quote! {
                 reserved_fields.add_field_function_get(
                     #accessor,
@@ -572,8 +1003,7 @@ fn build_variant_pieces(
                         if !active {
                             return Ok(::mlua::Value::Nil);
                         }
-                        let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                        let ctx = ::mlua_gen::make_resolver::<Self>(this, on_set);
+                        let ctx = <Self as ::mlua_gen::MluaGenProject>::root_resolver(lua, this)?;
                         let path = ::std::vec![::mlua_gen::PathStep::Variant(#accessor)];
                         let table = <Self as ::mlua_gen::MluaGenProject>::build_proxy(
                             lua, ctx, path, ::mlua_gen::Visibility::Both,
@@ -581,14 +1011,7 @@ fn build_variant_pieces(
                         Ok(::mlua::Value::Table(table))
                     },
                 );
-                reserved_fields.add_field_method_set(
-                    #accessor,
-                    |_, this, table: ::mlua::Table| {
-                        *this = Self::#variant_ident( #(#setter_indexed),* );
-                        #on_set_call
-                        Ok(())
-                    },
-                );
+                #variant_setter
             };
 
             let project_get_field_arms = field_idents
@@ -596,8 +1019,9 @@ fn build_variant_pieces(
                 .zip(field_tys.iter())
                 .zip(zero_based.iter())
                 .zip(zero_based_strs.iter())
+                .filter(|(_, s)| is_get(s))
                 .map(|(((id, ty), zb), s)| {
-                    let body = field_get_body(&quote!((*#id)), ty, s);
+                    let body = field_get_body(&quote!((*#id)), ty, &position_index(), s);
                     quote! { ::mlua_gen::PathStep::Tuple(#zb) => { #body } }
                 });
             let project_set_field_arms = field_idents
@@ -605,14 +1029,15 @@ fn build_variant_pieces(
                 .zip(field_tys.iter())
                 .zip(zero_based.iter())
                 .zip(zero_based_strs.iter())
+                .filter(|(_, s)| is_set(s))
                 .map(|(((id, ty), zb), s)| {
-                    let body = field_set_body(&quote!((*#id)), ty, s);
+                    let body = set_body(id, ty, s);
                     quote! { ::mlua_gen::PathStep::Tuple(#zb) => { #body } }
                 });
 
             let project_get_arm = quote! {
                 #accessor => {
-                    let Self::#variant_ident( #(#field_idents),* ) = self else {
+                    let Self::#variant_ident( #(#get_pattern),* ) = self else {
                         if rest.is_empty() {
                             return Ok(::mlua::Value::Nil);
                         }
@@ -622,7 +1047,7 @@ fn build_variant_pieces(
                     };
                     let Some((step, rest)) = rest.split_first() else {
                         let table = lua.create_table()?;
-                        #( let _ = table.push(#field_idents.to_owned()); )*
+                        #( table.set(#get_lua_indices, #get_copies?)?; )*
                         return ::mlua::IntoLua::into_lua(table, lua);
                     };
                     match step {
@@ -632,16 +1057,22 @@ fn build_variant_pieces(
                 },
             };
 
-            let setter_indexed2 = (1..=arity).map(|i| quote!(table.get(#i)?));
+            let replace_variant = if variant_settable {
+                quote! {
+                    let table: ::mlua::Table =
+                        ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
+                    *self = Self::#variant_ident( #(#fields_from_table),* );
+                    return Ok(());
+                }
+            } else {
+                not_settable(&accessor)
+            };
             let project_set_arm = quote! {
                 #accessor => {
                     if rest.is_empty() {
-                        let table: ::mlua::Table =
-                            ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                        *self = Self::#variant_ident( #(#setter_indexed2),* );
-                        return Ok(());
+                        #replace_variant
                     }
-                    let Self::#variant_ident( #(#field_idents),* ) = self else {
+                    let Self::#variant_ident( #(#set_pattern),* ) = self else {
                         return Err(::mlua::Error::runtime(
                             "variant changed under proxy",
                         ));
@@ -654,43 +1085,99 @@ fn build_variant_pieces(
                 },
             };
 
+            let project_tostring_field_arms = field_idents
+                .iter()
+                .zip(field_tys.iter())
+                .zip(zero_based.iter())
+                .zip(zero_based_strs.iter())
+                .filter(|(_, s)| is_get(s))
+                .map(|(((id, ty), zb), s)| {
+                    let body = field_tostring_body(&quote!((*#id)), ty, &position_index(), s);
+                    quote! { ::mlua_gen::PathStep::Tuple(#zb) => { #body } }
+                });
+            let project_tostring_arm = quote! {
+                #accessor => {
+                    if rest.is_empty() {
+                        return Ok(::mlua_gen::lua_tostring(self, ::mlua_gen::probe!(Self => display)));
+                    }
+                    let Self::#variant_ident( #(#get_pattern),* ) = self else {
+                        return Err(::mlua::Error::runtime(
+                            "variant changed under proxy",
+                        ));
+                    };
+                    let (step, rest) = rest.split_first().expect("checked above");
+                    match step {
+                        #(#project_tostring_field_arms)*
+                        _ => Err(::mlua_gen::bad_step(#accessor)),
+                    }
+                },
+            };
+
+            let project_keys_arm = unnamed_variant_walk_arm(
+                &accessor,
+                &variant_ident,
+                &get_pattern,
+                &field_idents,
+                &field_tys,
+                &get_positions,
+                VariantWalk::Keys,
+            );
+            let set_positions: Vec<usize> = zero_based
+                .iter()
+                .copied()
+                .filter(|zb| is_set(&zb.to_string()))
+                .collect();
+            let project_call_arm = unnamed_variant_walk_arm(
+                &accessor,
+                &variant_ident,
+                &set_pattern,
+                &field_idents,
+                &field_tys,
+                &set_positions,
+                VariantWalk::Call { owner: &owner },
+            );
+
             let proxy_index_arms = field_tys
                 .iter()
-                .zip(lua_indices.iter())
                 .zip(zero_based.iter())
-                .map(|((ty, lua_i), zb)| {
+                .filter(|(_, zb)| is_get(&zb.to_string()))
+                .map(|(ty, zb)| {
+                    let dispatch = proxy_index_dispatch(ty, &position_index());
                     quote! {
-                        #lua_i => {
+                        Some(#zb) => {
                             let mut p = path_g.clone();
                             p.push(::mlua_gen::PathStep::Tuple(#zb));
-                            proxy_index_dispatch::<#ty>(lua, ctx_g.clone(), p)
+                            #dispatch
                         }
                     }
                 });
             let proxy_newindex_arms = field_tys
                 .iter()
-                .zip(lua_indices.iter())
                 .zip(zero_based.iter())
-                .map(|((ty, lua_i), zb)| {
+                .filter(|(_, zb)| is_set(&zb.to_string()))
+                .map(|(_, zb)| {
                     quote! {
-                        #lua_i => {
+                        Some(#zb) => {
                             let mut p = path_s.clone();
                             p.push(::mlua_gen::PathStep::Tuple(#zb));
-                            proxy_newindex_dispatch::<#ty>(lua, ctx_s.clone(), p, value)
+                            ctx_s.set_and_fire(lua, &p, value)
                         }
                     }
                 });
+            let base = index_base_expr();
             let build_proxy_arm = quote! {
                 #accessor => {
                     let table = lua.create_table()?;
                     let mt = lua.create_table()?;
+                    ::mlua_gen::set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+                    ::mlua_gen::set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
 
                     let ctx_g = ctx.clone();
                     let path_g = path.clone();
                     mt.set(
                         "__index",
                         lua.create_function(move |lua, (_, key): (::mlua::Value, usize)| -> ::mlua::Result<::mlua::Value> {
-                            match key {
+                            match key.checked_sub(#base) {
                                 #(#proxy_index_arms)*
                                 _ => Err(::mlua::Error::runtime(::std::format!(
                                     "no such tuple field: {key}"
@@ -705,7 +1192,7 @@ fn build_variant_pieces(
                         mt.set(
                             "__newindex",
                             lua.create_function(move |lua, (_, key, value): (::mlua::Value, usize, ::mlua::Value)| -> ::mlua::Result<()> {
-                                match key {
+                                match key.checked_sub(#base) {
                                     #(#proxy_newindex_arms)*
                                     _ => Err(::mlua::Error::runtime(::std::format!(
                                         "no such tuple field: {key}"
@@ -726,6 +1213,9 @@ fn build_variant_pieces(
                 fields_arm,
                 project_get_arm,
                 project_set_arm,
+                project_tostring_arm,
+                project_keys_arm,
+                project_call_arm,
                 build_proxy_arm,
                 from_lua_match,
             }
@@ -773,6 +1263,18 @@ fn build_variant_pieces(
                     Ok(())
                 },
             };
+            let project_tostring_arm = quote! {
+                #accessor => {
+                    if !rest.is_empty() {
+                        return Err(::mlua_gen::bad_step(#accessor));
+                    }
+                    Ok(::mlua_gen::lua_tostring(self, ::mlua_gen::probe!(Self => display)))
+                },
+            };
+            let project_keys_arm = quote! {
+                #accessor => Err(::mlua_gen::bad_step(#accessor)),
+            };
+            let project_call_arm = project_keys_arm.clone();
             let build_proxy_arm = quote! {
                 #accessor => {
                     Err(::mlua::Error::runtime(
@@ -787,9 +1289,19 @@ fn build_variant_pieces(
                 fields_arm,
                 project_get_arm,
                 project_set_arm,
+                project_tostring_arm,
+                project_keys_arm,
+                project_call_arm,
                 build_proxy_arm,
                 from_lua_match,
             }
         },
     }
 }
+
+fn not_settable(accessor: &str) -> TokenStream2 {
+    let message = format!("variant `{accessor}` has fields that are not settable");
+    quote! {
+        return Err(::mlua::Error::runtime(#message));
+    }
+}
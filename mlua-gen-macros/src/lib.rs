@@ -1,5 +1,6 @@
 use {
-    attr::Attributes,
+    attr::{Attributes, FieldAccess, FieldAttributes, FieldsVisibility},
+    builder::SetHooks,
     proc_macro::TokenStream,
     proc_macro2::TokenStream as TokenStream2,
     quote::quote,
@@ -13,6 +14,7 @@ mod r#enum;
 pub(crate) mod project;
 mod shared;
 pub(crate) mod r#struct;
+mod stub;
 
 macro_rules! dbg {
     ($val:expr $(,)?) => {
@@ -63,7 +65,7 @@ macro_rules! dbg {
 
 #[proc_macro_attribute]
 pub fn mlua_gen(args: TokenStream, input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+    let mut input = parse_macro_input!(input as DeriveInput);
     let name = &input.ident;
     let generics = &input.generics;
 
@@ -74,8 +76,25 @@ pub fn mlua_gen(args: TokenStream, input: TokenStream) -> TokenStream {
     let code = match input.data {
         Data::Struct(ref ds) => {
             match (|| -> syn::Result<TokenStream2> {
-                let field_get = attributes.get.fields_from_visibility(&ds.fields)?;
-                let field_set = attributes.set.fields_from_visibility(&ds.fields)?;
+                let field_get = attributes
+                    .get
+                    .unwrap_or_default()
+                    .fields_from_visibility(&ds.fields, FieldAccess::Get)?;
+                let field_set = attributes
+                    .set
+                    .unwrap_or_default()
+                    .fields_from_visibility(&ds.fields, FieldAccess::Set)?;
+
+                let stub = stub::impl_stub_struct(
+                    name,
+                    generics,
+                    ds,
+                    &field_get,
+                    &field_set,
+                    &attributes.r#impl,
+                    &attributes.consts,
+                    attributes.index_base.unwrap_or(1),
+                );
 
                 let builder = r#struct::builder(
                     name,
@@ -85,7 +104,9 @@ pub fn mlua_gen(args: TokenStream, input: TokenStream) -> TokenStream {
                         .iter()
                         .filter(|fun| !fun.is_self)
                         .collect(),
+                    &attributes.consts,
                     generics,
+                    attributes.serde,
                 );
 
                 let user_data = r#struct::user_data(
@@ -97,11 +118,18 @@ pub fn mlua_gen(args: TokenStream, input: TokenStream) -> TokenStream {
                     field_set,
                     attributes.custom_fields,
                     attributes.r#impl,
+                    &attributes.consts,
                     attributes.custom_impls,
                     attributes.on_set,
+                    SetHooks {
+                        validate:  attributes.validate.as_ref(),
+                        on_change: attributes.on_change.as_ref(),
+                    },
+                    attributes.serde,
+                    attributes.index_base,
                 );
 
-                Ok(quote!(#builder #user_data))
+                Ok(quote!(#builder #user_data #stub))
             })() {
                 Ok(e) => dbg!(e),
                 // Ok(e) => e,
@@ -109,29 +137,83 @@ pub fn mlua_gen(args: TokenStream, input: TokenStream) -> TokenStream {
             }
         },
         Data::Enum(ref de) => {
-            let builder = r#enum::builder(
-                name,
-                de,
-                attributes
-                    .r#impl
+            if let Some(attr) = de
+                .variants
+                .iter()
+                .flat_map(|v| v.fields.iter())
+                .flat_map(|f| f.attrs.iter())
+                .find(|attr| attr.path().is_ident("mlua"))
+            {
+                return syn::Error::new_spanned(
+                    attr,
+                    "`#[mlua(...)]` is not supported on enum variant fields",
+                )
+                .into_compile_error()
+                .into();
+            }
+
+            match (|| -> syn::Result<TokenStream2> {
+                // Variant fields are as visible as the enum, so everything is exposed by default
+                let get = attributes.get.unwrap_or(FieldsVisibility::All);
+                let set = attributes.set.unwrap_or(FieldsVisibility::All);
+                let variants = de
+                    .variants
                     .iter()
-                    .filter(|fun| !fun.is_self)
-                    .collect(),
-                generics,
-            );
-            let user_data = r#enum::user_data(
-                name,
-                generics,
-                de.variants.iter(),
-                attributes.custom_fields,
-                attributes.custom_impls,
-                attributes.on_set,
-            );
-            quote!(#builder #user_data)
+                    .map(|variant| {
+                        Ok(r#enum::ExposedVariant {
+                            variant,
+                            get: get.variant_fields_from_visibility(&input.vis, variant)?,
+                            set: set.variant_fields_from_visibility(&input.vis, variant)?,
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+
+                let builder = r#enum::builder(
+                    name,
+                    de,
+                    attributes
+                        .r#impl
+                        .iter()
+                        .filter(|fun| !fun.is_self)
+                        .collect(),
+                    &attributes.consts,
+                    generics,
+                );
+                let stub = stub::impl_stub_enum(
+                    name,
+                    generics,
+                    &variants,
+                    &attributes.r#impl,
+                    &attributes.consts,
+                    attributes.index_base.unwrap_or(1),
+                );
+                let user_data = r#enum::user_data(
+                    name,
+                    generics,
+                    &variants,
+                    attributes.custom_fields,
+                    attributes.r#impl,
+                    attributes.custom_impls,
+                    attributes.on_set,
+                    SetHooks {
+                        validate:  attributes.validate.as_ref(),
+                        on_change: attributes.on_change.as_ref(),
+                    },
+                    attributes.serde,
+                    attributes.index_base,
+                );
+
+                Ok(quote!(#builder #user_data #stub))
+            })() {
+                Ok(e) => e,
+                Err(synerr) => return synerr.into_compile_error().into(),
+            }
         },
         Data::Union(_) => panic!("Must annotate struct or enum"),
     };
 
+    FieldAttributes::strip(&mut input.data);
+
     quote! {
         #input
 
@@ -2,10 +2,13 @@
 //! either recurses into the child's `project_*` or handles a leaf inline.
 
 use {
-    crate::{attr::MinimalField, shared::remove_ty_from_generics},
+    crate::{
+        attr::{FieldAttributes, FieldConstraints, MinimalField, position_index},
+        shared::remove_ty_from_generics,
+    },
     proc_macro2::TokenStream as TokenStream2,
     quote::quote,
-    syn::{Fields, Generics, Ident},
+    syn::{Field, Fields, Generics, Ident},
 };
 
 pub(crate) fn impl_project(
@@ -14,28 +17,50 @@ pub(crate) fn impl_project(
     all_fields: &Fields,
     get_fields: &[MinimalField],
     set_fields: &[MinimalField],
+    index_base: Option<usize>,
+    root_resolver: &TokenStream2,
 ) -> TokenStream2 {
     let non_typed_generics = remove_ty_from_generics(generics);
 
     let (get_arms, set_arms) = match all_fields {
-        Fields::Named(_) => named_arms(get_fields, set_fields),
-        Fields::Unnamed(_) => unnamed_arms(get_fields, set_fields),
+        Fields::Named(_) => named_arms(name, get_fields, set_fields),
+        Fields::Unnamed(_) => unnamed_arms(name, get_fields, set_fields),
         Fields::Unit => (quote!(), quote!()),
     };
 
+    let tostring_arms = field_arms(all_fields, get_fields, field_tostring_body);
+    let keys_arms = field_arms(all_fields, get_fields, field_keys_body);
+    let owner = name.to_string();
+    let call_arms = field_arms(all_fields, set_fields, |access, ty, index, field| {
+        field_call_body(access, ty, index, field, &owner)
+    });
+    let self_keys = self_keys(all_fields, get_fields);
     let build_proxy_body = build_proxy_body(all_fields, get_fields, set_fields);
+    let index_base = index_base_const(index_base);
+    let check_fields = field_checks(&owner, all_fields, &quote!(self), true).map(|checks| {
+        quote! {
+            fn check_fields(&mut self, lua: &::mlua::Lua) -> ::mlua::Result<()> {
+                #checks
+                Ok(())
+            }
+        }
+    });
 
     quote! {
         impl #generics ::mlua_gen::MluaGenProject for #name #non_typed_generics {
+            #index_base
+
+            #root_resolver
+
+            #check_fields
+
             fn project_get(
                 &self,
                 lua: &::mlua::Lua,
                 steps: &[::mlua_gen::PathStep],
             ) -> ::mlua::Result<::mlua::Value> {
                 let Some((step, rest)) = steps.split_first() else {
-                    return Err(::mlua::Error::runtime(
-                        "empty path: project_get on root must go through resolve_get",
-                    ));
+                    return ::mlua_gen::probe!(Self => lua_copy)(self, lua);
                 };
                 match step {
                     #get_arms
@@ -59,6 +84,50 @@ pub(crate) fn impl_project(
                 }
             }
 
+            fn project_tostring(
+                &self,
+                lua: &::mlua::Lua,
+                steps: &[::mlua_gen::PathStep],
+            ) -> ::mlua::Result<::std::string::String> {
+                let Some((step, rest)) = steps.split_first() else {
+                    return Ok(::mlua_gen::lua_tostring(self, ::mlua_gen::probe!(Self => display)));
+                };
+                match step {
+                    #tostring_arms
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                }
+            }
+
+            fn project_keys(
+                &self,
+                lua: &::mlua::Lua,
+                steps: &[::mlua_gen::PathStep],
+            ) -> ::mlua::Result<::mlua_gen::ProxyKeys> {
+                let Some((step, rest)) = steps.split_first() else {
+                    return #self_keys;
+                };
+                match step {
+                    #keys_arms
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                }
+            }
+
+            fn project_call(
+                &mut self,
+                lua: &::mlua::Lua,
+                steps: &[::mlua_gen::PathStep],
+                __mlua_gen_method: ::mlua_gen::CollectionMethod,
+                __mlua_gen_args: ::mlua::MultiValue,
+            ) -> ::mlua::Result<::mlua::Value> {
+                let Some((step, rest)) = steps.split_first() else {
+                    return Err(::mlua_gen::bad_step(stringify!(#name)));
+                };
+                match step {
+                    #call_arms
+                    _ => Err(::mlua_gen::bad_step(stringify!(#name))),
+                }
+            }
+
             fn build_proxy(
                 lua: &::mlua::Lua,
                 ctx: ::mlua_gen::Resolver,
@@ -71,7 +140,21 @@ pub(crate) fn impl_project(
     }
 }
 
+/// `const INDEX_BASE` of the `MluaGenProject` impl, when `index_base = ..` is set.
+pub(crate) fn index_base_const(index_base: Option<usize>) -> TokenStream2 {
+    match index_base {
+        Some(base) => quote!(const INDEX_BASE: usize = #base;),
+        None => quote!(),
+    }
+}
+
+/// Lua position of the first tuple field or sequence element of `Self`.
+pub(crate) fn index_base_expr() -> TokenStream2 {
+    quote!(<Self as ::mlua_gen::MluaGenProject>::INDEX_BASE)
+}
+
 fn named_arms(
+    name: &Ident,
     get_fields: &[MinimalField],
     set_fields: &[MinimalField],
 ) -> (TokenStream2, TokenStream2) {
@@ -79,17 +162,25 @@ fn named_arms(
         let ident = &f.ident;
         let ty = &f.ty;
         let name = &f.ident_string;
-        let body = field_get_body(&quote!(self.#ident), ty, name);
+        let body = field_get_body(&quote!(self.#ident), ty, &f.index, name);
         quote! {
             ::mlua_gen::PathStep::Field(#name) => { #body }
         }
     });
 
+    let owner = name.to_string();
     let set = set_fields.iter().map(|f| {
         let ident = &f.ident;
         let ty = &f.ty;
         let name = &f.ident_string;
-        let body = field_set_body(&quote!(self.#ident), ty, name);
+        let body = field_set_body(
+            &quote!(self.#ident),
+            ty,
+            &f.index,
+            name,
+            &owner,
+            &f.constraints,
+        );
         quote! {
             ::mlua_gen::PathStep::Field(#name) => { #body }
         }
@@ -98,6 +189,53 @@ fn named_arms(
     (quote!(#(#get)*), quote!(#(#set)*))
 }
 
+/// One `body` arm per field of `fields`, for the walkers that don't handle
+/// fields differently (`project_tostring`, `project_keys`, `project_call`).
+fn field_arms<B>(all_fields: &Fields, fields: &[MinimalField], body: B) -> TokenStream2
+where
+    B: Fn(&TokenStream2, &syn::Type, &syn::Type, &str) -> TokenStream2,
+{
+    let arms = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let name = &f.ident_string;
+        let body = body(&quote!(self.#ident), &f.ty, &f.index, name);
+        match all_fields {
+            Fields::Unnamed(_) => {
+                let parsed: usize = name.parse().expect("tuple field must be numeric");
+                quote! { ::mlua_gen::PathStep::Tuple(#parsed) => { #body } }
+            },
+            Fields::Named(_) | Fields::Unit => {
+                quote! { ::mlua_gen::PathStep::Field(#name) => { #body } }
+            },
+        }
+    });
+
+    quote!(#(#arms)*)
+}
+
+/// `ProxyKeys` of the struct itself: its readable fields.
+pub(crate) fn self_keys(all_fields: &Fields, get_fields: &[MinimalField]) -> TokenStream2 {
+    match all_fields {
+        Fields::Unnamed(_) => {
+            let zero_based = get_fields.iter().map(|f| {
+                f.ident_string
+                    .parse::<usize>()
+                    .expect("tuple field must be numeric")
+            });
+            let base = index_base_expr();
+            quote! {
+                Ok(::mlua_gen::ProxyKeys::Keys(::std::vec![
+                    #(::mlua::IntoLua::into_lua(#zero_based + #base, lua)?),*
+                ]))
+            }
+        },
+        Fields::Named(_) | Fields::Unit => {
+            let names = get_fields.iter().map(|f| &f.ident_string);
+            quote!(Ok(::mlua_gen::ProxyKeys::Fields(&[#(#names),*])))
+        },
+    }
+}
+
 fn build_proxy_body(
     all_fields: &Fields,
     get_fields: &[MinimalField],
@@ -114,96 +252,49 @@ fn build_proxy_body(
     }
 }
 
-/// Helper-fn definitions shared by every proxy `__index` / `__newindex`
-/// closure: monomorphised per field type so the const probes prune to a
-/// single arm at codegen.
-pub(crate) fn proxy_dispatch_helpers() -> TokenStream2 {
+/// What the `__index` of a proxy returns for a field of type `ty`, `p` being the path of the
+/// field: a sub-proxy, or the value itself for leaves.
+pub(crate) fn proxy_index_dispatch(ty: &syn::Type, index: &syn::Type) -> TokenStream2 {
     quote! {
-        fn proxy_index_dispatch<Ty>(
-            lua: &::mlua::Lua,
-            ctx: ::mlua_gen::Resolver,
-            p: ::std::vec::Vec<::mlua_gen::PathStep>,
-        ) -> ::mlua::Result<::mlua::Value>
-        where
-            Ty: ::mlua_gen::MluaGenProjectMaybe
-                + ::mlua_gen::IsMluaGenerated
-                + ::mlua_gen::CollectionProject
-                + ::mlua_gen::IsIndexable,
-        {
-            match (
-                <Ty as ::mlua_gen::IsMluaGenerated>::IS_MLUA_GENERATED,
-                <Ty as ::mlua_gen::CollectionProject>::IS_COLLECTION_OF_MLUA_GEN,
-                <Ty as ::mlua_gen::IsIndexable>::IS_INDEXABLE,
-            ) {
-                (true, _, _) => Ok(::mlua::Value::Table(
-                    <Ty as ::mlua_gen::MluaGenProjectMaybe>::maybe_build_proxy(
-                        lua, ctx, p, ::mlua_gen::Visibility::Both,
-                    )?,
-                )),
-                (_, true, _) => Ok(::mlua::Value::Table(
-                    <Ty as ::mlua_gen::CollectionProject>::build_collection_proxy(
-                        lua, ctx, p, ::mlua_gen::Visibility::Both,
-                    )?,
-                )),
-                (_, _, true) => Ok(::mlua::Value::Table(
-                    ::mlua_gen::build_indexed_proxy_leaf(
-                        lua, ctx, p, ::mlua_gen::Visibility::Both,
-                    )?,
-                )),
-                (false, false, false) => (ctx.get)(lua, &p),
-            }
-        }
-
-        fn proxy_newindex_dispatch<Ty>(
-            lua: &::mlua::Lua,
-            ctx: ::mlua_gen::Resolver,
-            p: ::std::vec::Vec<::mlua_gen::PathStep>,
-            value: ::mlua::Value,
-        ) -> ::mlua::Result<()>
-        where
-            Ty: ::mlua_gen::MluaGenProjectMaybe
-                + ::mlua_gen::IsMluaGenerated
-                + ::mlua_gen::CollectionProject
-                + ::mlua_gen::IsIndexable,
-        {
-            (ctx.set)(lua, &p, value)?;
-            ctx.fire_on_set();
-            Ok(())
+        match ::mlua_gen::probe!(#ty, #index => field_ops).proxy {
+            Some(proxy) => proxy(lua, ctx_g.clone(), p, ::mlua_gen::Visibility::Both),
+            None => (ctx_g.get)(lua, &p),
         }
     }
 }
 
 fn build_proxy_named(get_fields: &[MinimalField], set_fields: &[MinimalField]) -> TokenStream2 {
     let index_arms = get_fields.iter().map(|f| {
-        let ty = &f.ty;
         let name = &f.ident_string;
+        let dispatch = proxy_index_dispatch(&f.ty, &f.index);
         quote! {
             #name => {
                 let mut p = path_g.clone();
                 p.push(::mlua_gen::PathStep::Field(#name));
-                proxy_index_dispatch::<#ty>(lua, ctx_g.clone(), p)
+                #dispatch
             }
         }
     });
 
     let newindex_arms = set_fields.iter().map(|f| {
-        let ty = &f.ty;
         let name = &f.ident_string;
         quote! {
             #name => {
                 let mut p = path_s.clone();
                 p.push(::mlua_gen::PathStep::Field(#name));
-                proxy_newindex_dispatch::<#ty>(lua, ctx_s.clone(), p, value)
+                ctx_s.set_and_fire(lua, &p, value)
             }
         }
     });
 
-    let helpers = proxy_dispatch_helpers();
     quote! {
-        #helpers
-
         let table = lua.create_table()?;
         let mt = lua.create_table()?;
+        ::mlua_gen::set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+        ::mlua_gen::set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
+        ::mlua_gen::set_proxy_operators::<Self>(
+            lua, &mt, ctx.clone(), path.clone(), ::mlua_gen::operators!(Self),
+        )?;
 
         let ctx_g = ctx.clone();
         let path_g = path.clone();
@@ -245,46 +336,46 @@ fn build_proxy_named(get_fields: &[MinimalField], set_fields: &[MinimalField]) -
 }
 
 fn build_proxy_unnamed(get_fields: &[MinimalField], set_fields: &[MinimalField]) -> TokenStream2 {
-    // Lua keys are 1-based; PathStep::Tuple is 0-based.
+    // Lua keys start at `INDEX_BASE`; PathStep::Tuple is 0-based.
     let index_arms = get_fields.iter().map(|f| {
-        let ty = &f.ty;
+        let dispatch = proxy_index_dispatch(&f.ty, &f.index);
         let zero_based: usize = f.ident_string.parse().expect("tuple field must be numeric");
-        let lua_index: usize = zero_based + 1;
         quote! {
-            #lua_index => {
+            Some(#zero_based) => {
                 let mut p = path_g.clone();
                 p.push(::mlua_gen::PathStep::Tuple(#zero_based));
-                proxy_index_dispatch::<#ty>(lua, ctx_g.clone(), p)
+                #dispatch
             }
         }
     });
 
     let newindex_arms = set_fields.iter().map(|f| {
-        let ty = &f.ty;
         let zero_based: usize = f.ident_string.parse().expect("tuple field must be numeric");
-        let lua_index: usize = zero_based + 1;
         quote! {
-            #lua_index => {
+            Some(#zero_based) => {
                 let mut p = path_s.clone();
                 p.push(::mlua_gen::PathStep::Tuple(#zero_based));
-                proxy_newindex_dispatch::<#ty>(lua, ctx_s.clone(), p, value)
+                ctx_s.set_and_fire(lua, &p, value)
             }
         }
     });
 
-    let helpers = proxy_dispatch_helpers();
+    let base = index_base_expr();
     quote! {
-        #helpers
-
         let table = lua.create_table()?;
         let mt = lua.create_table()?;
+        ::mlua_gen::set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+        ::mlua_gen::set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
+        ::mlua_gen::set_proxy_operators::<Self>(
+            lua, &mt, ctx.clone(), path.clone(), ::mlua_gen::operators!(Self),
+        )?;
 
         let ctx_g = ctx.clone();
         let path_g = path.clone();
         mt.set(
             "__index",
             lua.create_function(move |lua, (_, key): (::mlua::Value, usize)| -> ::mlua::Result<::mlua::Value> {
-                match key {
+                match key.checked_sub(#base) {
                     #(#index_arms)*
                     _ => Err(::mlua::Error::runtime(::std::format!(
                         "no such tuple field: {key}"
@@ -299,7 +390,7 @@ fn build_proxy_unnamed(get_fields: &[MinimalField], set_fields: &[MinimalField])
             mt.set(
                 "__newindex",
                 lua.create_function(move |lua, (_, key, value): (::mlua::Value, usize, ::mlua::Value)| -> ::mlua::Result<()> {
-                    match key {
+                    match key.checked_sub(#base) {
                         #(#newindex_arms)*
                         _ => Err(::mlua::Error::runtime(::std::format!(
                             "no such tuple field: {key}"
@@ -315,6 +406,7 @@ fn build_proxy_unnamed(get_fields: &[MinimalField], set_fields: &[MinimalField])
 }
 
 fn unnamed_arms(
+    name: &Ident,
     get_fields: &[MinimalField],
     set_fields: &[MinimalField],
 ) -> (TokenStream2, TokenStream2) {
@@ -322,17 +414,25 @@ fn unnamed_arms(
         let ident = &f.ident;
         let ty = &f.ty;
         let parsed: usize = f.ident_string.parse().expect("tuple field must be numeric");
-        let body = field_get_body(&quote!(self.#ident), ty, &f.ident_string);
+        let body = field_get_body(&quote!(self.#ident), ty, &f.index, &f.ident_string);
         quote! {
             ::mlua_gen::PathStep::Tuple(#parsed) => { #body }
         }
     });
 
+    let owner = name.to_string();
     let set = set_fields.iter().map(|f| {
         let ident = &f.ident;
         let ty = &f.ty;
         let parsed: usize = f.ident_string.parse().expect("tuple field must be numeric");
-        let body = field_set_body(&quote!(self.#ident), ty, &f.ident_string);
+        let body = field_set_body(
+            &quote!(self.#ident),
+            ty,
+            &f.index,
+            &f.ident_string,
+            &owner,
+            &f.constraints,
+        );
         quote! {
             ::mlua_gen::PathStep::Tuple(#parsed) => { #body }
         }
@@ -341,113 +441,184 @@ fn unnamed_arms(
     (quote!(#(#get)*), quote!(#(#set)*))
 }
 
-pub(crate) fn field_get_body(access: &TokenStream2, ty: &syn::Type, name: &str) -> TokenStream2 {
+/// `probe!(ty, index => field_ops)`, how the walkers reach under a field of type `ty`.
+fn field_ops(ty: &syn::Type, index: &syn::Type) -> TokenStream2 {
+    quote!(::mlua_gen::probe!(#ty, #index => field_ops))
+}
+
+/// `index` is the `Idx` of the field's `Index<Idx>`, see [`MinimalField::index`].
+pub(crate) fn field_get_body(
+    access: &TokenStream2,
+    ty: &syn::Type,
+    index: &syn::Type,
+    name: &str,
+) -> TokenStream2 {
+    let base = index_base_expr();
+    let ops = field_ops(ty, index);
+    let into_lua = field_into_lua(&quote!(#access.clone()), ty);
     quote! {
-        match (
-            <#ty as ::mlua_gen::IsMluaGenerated>::IS_MLUA_GENERATED,
-            <#ty as ::mlua_gen::CollectionProject>::IS_COLLECTION_OF_MLUA_GEN,
-            <#ty as ::mlua_gen::IsIndexable>::IS_INDEXABLE,
-        ) {
-            (true, _, _) => <#ty as ::mlua_gen::MluaGenProjectMaybe>::maybe_project_get(&#access, lua, rest),
-            (_, true, _) => match rest.split_first() {
-                None => ::mlua::IntoLua::into_lua(#access.clone(), lua),
-                Some((::mlua_gen::PathStep::Index(k), rest2)) => {
-                    <#ty as ::mlua_gen::CollectionProject>::project_get_elem(
-                        &#access, lua, k.clone(), rest2,
-                    )
-                },
-                Some(_) => Err(::mlua_gen::bad_step(#name)),
-            },
-            (_, _, true) => match rest.split_first() {
-                None => ::mlua::IntoLua::into_lua(#access.clone(), lua),
-                Some((::mlua_gen::PathStep::Index(k), rest2)) if rest2.is_empty() => {
-                    use ::mlua_gen::IsIndexable;
-                    let one_based: usize = ::mlua::FromLua::from_lua(k.clone(), lua)?;
-                    let idx = one_based
-                        .checked_sub(1)
-                        .ok_or_else(|| ::mlua::Error::runtime("Lua indices start at 1"))?;
-                    ::mlua::IntoLua::into_lua(#access.index_or_unreachable(idx), lua)
-                },
-                Some(_) => Err(::mlua_gen::bad_step(#name)),
-            },
-            (false, false, false) => {
-                if rest.is_empty() {
-                    ::mlua::IntoLua::into_lua(#access.clone(), lua)
-                } else {
-                    Err(::mlua_gen::bad_step(#name))
-                }
-            },
+        if rest.is_empty() {
+            #into_lua
+        } else {
+            (#ops.get)(&#access, lua, #base, #name, rest)
         }
     }
 }
 
-pub(crate) fn field_set_body(access: &TokenStream2, ty: &syn::Type, name: &str) -> TokenStream2 {
+/// `probe!(ty => field_into_lua)` of `value`, a copy of a field of type `ty`.
+pub(crate) fn field_into_lua(value: &TokenStream2, ty: &syn::Type) -> TokenStream2 {
+    quote!(::mlua_gen::probe!(#ty => field_into_lua)(#value, lua))
+}
+
+/// `&str` of the field `name` in a Lua path, for the errors of the constraints: `.name`, or
+/// `[position]` for a tuple field.
+pub(crate) fn lua_step(name: &str) -> TokenStream2 {
+    match name.parse::<usize>() {
+        Ok(zero_based) => {
+            let base = index_base_expr();
+            quote!(&::std::format!("[{}]", #zero_based + #base))
+        },
+        Err(_) => {
+            let step = format!(".{name}");
+            quote!(#step)
+        },
+    }
+}
+
+/// `probe!(ty => field_from_lua)` of `value`, for the field `name` of `owner`: the errors of the
+/// constraints under the value go through the field. Without the `?`.
+pub(crate) fn field_from_lua(
+    value: &TokenStream2,
+    ty: &syn::Type,
+    owner: &str,
+    name: &str,
+) -> TokenStream2 {
+    let step = lua_step(name);
     quote! {
-        match (
-            <#ty as ::mlua_gen::IsMluaGenerated>::IS_MLUA_GENERATED,
-            <#ty as ::mlua_gen::CollectionProject>::IS_COLLECTION_OF_MLUA_GEN,
-            <#ty as ::mlua_gen::IsIndexable>::IS_INDEXABLE,
-        ) {
-            (true, _, _) => {
-                if rest.is_empty() {
-                    #access = ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                    Ok(())
-                } else {
-                    <#ty as ::mlua_gen::MluaGenProjectMaybe>::maybe_project_set(
-                        &mut #access, lua, rest, __mlua_gen_value,
-                    )
-                }
-            },
-            (_, true, _) => match rest.split_first() {
-                None => {
-                    #access = ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                    Ok(())
-                },
-                Some((::mlua_gen::PathStep::Index(k), rest2)) => {
-                    <#ty as ::mlua_gen::CollectionProject>::project_set_elem(
-                        &mut #access, lua, k.clone(), rest2, __mlua_gen_value,
-                    )
+        ::mlua_gen::probe!(#ty => field_from_lua)(#value, lua)
+            .map_err(|err| ::mlua_gen::within_field(err, #owner, #step))
+    }
+}
+
+/// Checks of the constraints of `fields` on `this.field`, `this` being a `&mut` of their owner,
+/// see [`FieldConstraints::check`]. With `nested`, the `mlua_gen` values under the fields are
+/// checked too, see `MluaGenProject::check_fields`. `None` when there is nothing to check.
+pub(crate) fn field_checks<'a, I>(
+    owner: &str,
+    fields: I,
+    this: &TokenStream2,
+    nested: bool,
+) -> Option<TokenStream2>
+where
+    I: IntoIterator<Item = &'a Field>,
+{
+    let base = index_base_expr();
+    let checks = fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(idx, field)| {
+            // Errors are reported by `fields_from_visibility`
+            let attributes = FieldAttributes::parse(field).unwrap_or_default();
+            if attributes.skip || (!nested && attributes.constraints == FieldConstraints::default())
+            {
+                return None;
+            }
+
+            let (member, name) = match &field.ident {
+                Some(ident) => {
+                    let name = attributes.rename.unwrap_or_else(|| ident.to_string());
+                    (quote!(#ident), name)
                 },
-                Some(_) => Err(::mlua_gen::bad_step(#name)),
-            },
-            (_, _, true) => match rest.split_first() {
                 None => {
-                    #access = ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                    Ok(())
-                },
-                Some((::mlua_gen::PathStep::Index(k), rest2)) if rest2.is_empty() => {
-                    if <#ty as ::mlua_gen::IsNewIndexable>::IS_NEW_INDEXABLE {
-                        use ::mlua_gen::IsNewIndexable;
-                        let key: <#ty as IsNewIndexable>::Key =
-                            ::mlua::FromLua::from_lua(k.clone(), lua)?;
-                        let item: <#ty as IsNewIndexable>::Item =
-                            ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                        #access.set_index_or_unreachable(key, item);
-                        Ok(())
-                    } else if <#ty as ::mlua_gen::IsMutIndexable>::IS_MUT_INDEXABLE {
-                        use ::mlua_gen::IsMutIndexable;
-                        let one_based: usize = ::mlua::FromLua::from_lua(k.clone(), lua)?;
-                        let idx = one_based
-                            .checked_sub(1)
-                            .ok_or_else(|| ::mlua::Error::runtime("Lua indices start at 1"))?;
-                        let item: <#ty as IsMutIndexable>::IndexType =
-                            ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                        #access.set_index_or_unreachable(idx, item);
-                        Ok(())
-                    } else {
-                        Err(::mlua_gen::bad_step(#name))
-                    }
+                    let position = syn::Index::from(idx);
+                    (quote!(#position), idx.to_string())
                 },
-                Some(_) => Err(::mlua_gen::bad_step(#name)),
-            },
-            (false, false, false) => {
-                if rest.is_empty() {
-                    #access = ::mlua::FromLua::from_lua(__mlua_gen_value, lua)?;
-                    Ok(())
-                } else {
-                    Err(::mlua_gen::bad_step(#name))
+            };
+            let step = lua_step(&name);
+            let place = quote!(#this.#member);
+            let check = attributes.constraints.check(owner, &step, &place);
+            let nested = nested.then(|| {
+                let ops = field_ops(&field.ty, &attributes.index.unwrap_or_else(position_index));
+                quote! {
+                    (#ops.check)(&mut #place, lua, #base)
+                        .map_err(|err| ::mlua_gen::within_field(err, #owner, #step))?;
                 }
-            },
+            });
+            Some(quote!(#check #nested))
+        })
+        .collect::<Vec<_>>();
+
+    (!checks.is_empty()).then(|| quote!(#(#checks)*))
+}
+
+/// `owner` is the Lua name of the type holding the field, used by the errors of the
+/// `constraints`.
+pub(crate) fn field_set_body(
+    access: &TokenStream2,
+    ty: &syn::Type,
+    index: &syn::Type,
+    name: &str,
+    owner: &str,
+    constraints: &FieldConstraints,
+) -> TokenStream2 {
+    let base = index_base_expr();
+    let ops = field_ops(ty, index);
+    let step = lua_step(name);
+    let from_lua = field_from_lua(&quote!(__mlua_gen_value), ty, owner, name);
+    let write = constraints.write(owner, &step, access, ty, &quote!(#from_lua?));
+
+    quote! {
+        if rest.is_empty() {
+            #write
+            Ok(())
+        } else {
+            (#ops.set)(&mut #access, lua, #base, #name, rest, __mlua_gen_value)
+                .map_err(|err| ::mlua_gen::within_field(err, #owner, #step))
+        }
+    }
+}
+
+pub(crate) fn field_tostring_body(
+    access: &TokenStream2,
+    ty: &syn::Type,
+    index: &syn::Type,
+    name: &str,
+) -> TokenStream2 {
+    let base = index_base_expr();
+    let ops = field_ops(ty, index);
+    quote! {
+        if rest.is_empty() {
+            Ok(::mlua_gen::lua_tostring(&#access, ::mlua_gen::probe!(#ty => display)))
+        } else {
+            (#ops.tostring)(&#access, lua, #base, #name, rest)
         }
     }
 }
+
+pub(crate) fn field_keys_body(
+    access: &TokenStream2,
+    ty: &syn::Type,
+    index: &syn::Type,
+    name: &str,
+) -> TokenStream2 {
+    let base = index_base_expr();
+    let ops = field_ops(ty, index);
+    quote!((#ops.keys)(&#access, lua, #base, #name, rest))
+}
+
+/// `owner` is the Lua name of the type holding the field, see [`field_set_body`].
+pub(crate) fn field_call_body(
+    access: &TokenStream2,
+    ty: &syn::Type,
+    index: &syn::Type,
+    name: &str,
+    owner: &str,
+) -> TokenStream2 {
+    let base = index_base_expr();
+    let ops = field_ops(ty, index);
+    let step = lua_step(name);
+    quote! {
+        (#ops.call)(&mut #access, lua, #base, #name, rest, __mlua_gen_method, __mlua_gen_args)
+            .map_err(|err| ::mlua_gen::within_field(err, #owner, #step))
+    }
+}
@@ -1,14 +1,30 @@
 use {
     crate::{
         attr::{MethodOrFunction, MinimalField},
-        builder::{builder_for_fields, builder_for_functions, generate_tuple_access},
-        project::impl_project,
+        builder::{
+            SetHooks,
+            builder_for_fields,
+            builder_for_functions,
+            checked_constructor,
+            meta_methods,
+            named_fields_from_table,
+            registration,
+            user_data_methods,
+        },
+        project::{
+            field_from_lua,
+            field_into_lua,
+            impl_project,
+            index_base_expr,
+            lua_step,
+            self_keys,
+        },
         shared::remove_ty_from_generics,
     },
-    proc_macro2::{Span, TokenStream as TokenStream2},
-    quote::{ToTokens, quote},
-    std::{collections::HashSet, iter::repeat_with},
-    syn::{DataStruct, Field, Fields, Generics, Ident, Path, parse_str},
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    std::collections::HashSet,
+    syn::{DataStruct, Fields, Generics, Ident, Path},
 };
 
 /// Function that impl the `mlua_gen::LuaBuilder` trait for a struct
@@ -16,27 +32,34 @@ pub fn builder(
     name: &Ident,
     ds: &DataStruct,
     functions: Vec<&MethodOrFunction>,
+    consts: &[Ident],
     generics: &Generics,
+    serde: bool,
 ) -> TokenStream2 {
-    let init_builder_code = builder_for_fields(&quote! {Self}, &ds.fields, false);
-    let maybe_set_metatable = if ds.fields == Fields::Unit {
-        quote!()
+    let owner = name.to_string();
+    let init_builder_code = builder_for_fields(&quote! {Self}, &owner, &ds.fields, false, serde);
+    // A unit struct stays its value, its functions and consts being registered on the userdata
+    // with a `__call` building it. The other structs get the table of their functions and consts
+    // once they have any, the constructor moving to its `__call`.
+    let to_table_code = if ds.fields == Fields::Unit {
+        quote!(target.set(key, Self::lua_builder(&lua)?)?;)
     } else {
-        let function_wrap_builder_code = builder_for_fields(&quote! {Self}, &ds.fields, true);
-
+        let call_builder_code = builder_for_fields(&quote! {Self}, &owner, &ds.fields, true, serde);
         quote! {
-            table.set_metatable(Some({
+            if let Some(table) = Self::lua_fn_builder(&lua)? {
                 let metatable = lua.create_table()?;
-                metatable.set(
-                    "__call",
-                    #function_wrap_builder_code,
-                )?;
-                metatable
-            }));
+                metatable.set("__call", #call_builder_code?)?;
+                table.set_metatable(Some(metatable));
+
+                target.set(key, table)?;
+            } else {
+                target.set(key, Self::lua_builder(&lua)?)?;
+            }
         }
     };
-    let builder_fn_code = builder_for_functions(&quote! {Self}, functions);
+    let builder_fn_code = builder_for_functions(&quote! {Self}, functions, consts);
     let no_ty_generics = remove_ty_from_generics(generics);
+    let registration = registration(name);
 
     // The reason for that is that, when we have a unit struct, we just want to be able to call it
     // like normal:
@@ -69,28 +92,19 @@ pub fn builder(
                 #builder_fn_code
             }
 
-            fn to_globals(lua: &::mlua::Lua) -> ::mlua::Result<()> {
-                Self::to_globals_as(lua, stringify!(#name))
-            }
+            #registration
 
-            fn to_globals_as<S: AsRef<str>>(lua: &::mlua::Lua, s: S) -> ::mlua::Result<()> {
-                // When there are no function constructors (`new`, `default`, etc.), we can just
-                // put it as a basic `function` (Cf. `else` block). But when it's not, we need to
-                // create a metatable just for that.
-                if let Some(table) = Self::lua_fn_builder(&lua)? {
-                    #maybe_set_metatable
-
-                    lua.globals().set(s.as_ref(), table)?;
-                } else {
-                    lua.globals()
-                        .set(s.as_ref(), Self::lua_builder(&lua)?)?;
-                }
+            fn to_table_as<S: AsRef<str>>(
+                lua: &::mlua::Lua,
+                target: &::mlua::Table,
+                s: S,
+            ) -> ::mlua::Result<()> {
+                let (target, key) = ::mlua_gen::nested_table(lua, target, s.as_ref())?;
+                #to_table_code
 
                 Ok(())
             }
         }
-
-        impl #generics ::mlua_gen::AutomaticImplWhenMluaGen for #name #no_ty_generics { }
     }
 }
 
@@ -103,21 +117,19 @@ pub(crate) fn user_data(
     set_fields: Vec<MinimalField>,
     custom_field: Option<syn::Ident>,
     impls: Vec<MethodOrFunction>,
+    consts: &[Ident],
     custom_method_or_fn: Option<syn::Ident>,
     on_set: Option<Path>,
+    hooks: SetHooks,
+    serde: bool,
+    index_base: Option<usize>,
 ) -> TokenStream2 {
     let on_set_call = match &on_set {
         Some(path) => quote!( (#path)(); ),
         None => quote!(),
     };
-    let on_set_resolver = match &on_set {
-        Some(path) => {
-            quote! {
-                ::std::option::Option::Some(::std::sync::Arc::new(|| { (#path)(); }) as ::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>)
-            }
-        },
-        None => quote!(::std::option::Option::None),
-    };
+    let root_resolver = hooks.root_resolver(on_set.as_ref());
+    let owner = name.to_string();
     let (fields_declaration, meta_index) = match all_fields {
         Fields::Named(_) => {
             let get_and_set_fields = get_fields
@@ -133,118 +145,87 @@ pub(crate) fn user_data(
                 let field_ident = &field.ident;
                 let field_as_string = &field.ident_string;
                 let field_ty = &field.ty;
-
-                let base_code = quote!(
-                    match (
-                        <#field_ty as ::mlua_gen::IsMluaGenerated>::IS_MLUA_GENERATED,
-                        <#field_ty as ::mlua_gen::CollectionProject>::IS_COLLECTION_OF_MLUA_GEN,
-                        <#field_ty as ::mlua_gen::IsIndexable>::IS_INDEXABLE,
-                    ) {
-                        // Nested `#[mlua_gen]` field — recurse via build_proxy.
-                        (true, _, _) => {
-                            if #is_get {
-                                reserved_fields.add_field_function_get(#field_as_string, |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
-                                    let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                                    let ctx = ::mlua_gen::make_resolver::<Self>(this, on_set);
-                                    let path = ::std::vec![::mlua_gen::PathStep::Field(#field_as_string)];
-                                    let vis = if #is_set {
-                                        ::mlua_gen::Visibility::Both
-                                    } else {
-                                        ::mlua_gen::Visibility::GetOnly
-                                    };
-                                    let table = <#field_ty as ::mlua_gen::MluaGenProjectMaybe>::maybe_build_proxy(lua, ctx, path, vis)?;
-                                    Ok(::mlua::Value::Table(table))
-                                });
-                            }
-                            // Whole-field replacement: outer.inner = { ... }
-                            if #is_set {
-                                reserved_fields.add_field_method_set(
-                                    #field_as_string,
-                                    |_, this, v: #field_ty| {
-                                        this.#field_ident = v;
-                                        #on_set_call
-                                        Ok(())
-                                    }
-                                );
-                            }
+                let field_index = &field.index;
+                let step = lua_step(field_as_string);
+                let field_setter = if hooks.is_empty() {
+                    let write = field.constraints.write(
+                        &owner,
+                        &step,
+                        &quote!(this.#field_ident),
+                        field_ty,
+                        &quote!(v),
+                    );
+                    let from_lua = field_from_lua(&quote!(v), field_ty, &owner, field_as_string);
+                    quote! {
+                        |lua, this, v: ::mlua::Value| {
+                            let v = #from_lua?;
+                            #write
+                            #on_set_call
+                            Ok(())
                         }
-                        // Collection of `mlua_gen` elements.
-                        (_, true, _) => {
-                            if #is_get {
-                                reserved_fields.add_field_function_get(#field_as_string, |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
-                                    let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                                    let ctx = ::mlua_gen::make_resolver::<Self>(this, on_set);
-                                    let path = ::std::vec![::mlua_gen::PathStep::Field(#field_as_string)];
-                                    let vis = if #is_set {
-                                        ::mlua_gen::Visibility::Both
-                                    } else {
-                                        ::mlua_gen::Visibility::GetOnly
-                                    };
-                                    let table = <#field_ty as ::mlua_gen::CollectionProject>::build_collection_proxy(lua, ctx, path, vis)?;
-                                    Ok(::mlua::Value::Table(table))
-                                });
-                            }
-                            if #is_set {
-                                reserved_fields.add_field_method_set(
-                                    #field_as_string,
-                                    |_, this, v: #field_ty| {
-                                        this.#field_ident = v;
-                                        #on_set_call
-                                        Ok(())
-                                    }
-                                );
-                            }
+                    }
+                } else {
+                    let from_lua =
+                        field_from_lua(&quote!(new.clone()), field_ty, &owner, field_as_string);
+                    let assign = field.constraints.write(
+                        &owner,
+                        &step,
+                        &quote!(this.#field_ident),
+                        field_ty,
+                        &quote!(#from_lua?),
+                    );
+                    let write = hooks.around(
+                        &quote!(::mlua_gen::PathStep::Field(#field_as_string)),
+                        &quote!(&this.#field_ident),
+                        field_ty,
+                        &assign,
+                        field.constraints.clamp.is_some(),
+                    );
+                    quote! {
+                        |lua, this, new: ::mlua::Value| {
+                            #write
+                            #on_set_call
+                            Ok(())
                         }
-                        // Indexable collection of leaf values.
-                        (_, _, true) => {
+                    }
+                };
+
+                let field_copy = field_into_lua(&quote!(this.#field_ident.clone()), field_ty);
+                let base_code = quote!(
+                    match ::mlua_gen::probe!(#field_ty, #field_index => field_ops).proxy {
+                        // Nested `#[mlua_gen]` field or collection, proxied through the resolver.
+                        Some(proxy) => {
                             if #is_get {
-                                reserved_fields.add_field_function_get(#field_as_string, |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
-                                    let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                                    let ctx = ::mlua_gen::make_resolver::<Self>(this, on_set);
+                                reserved_fields.add_field_function_get(#field_as_string, move |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
+                                    let ctx = <Self as ::mlua_gen::MluaGenProject>::root_resolver(lua, this)?;
                                     let path = ::std::vec![::mlua_gen::PathStep::Field(#field_as_string)];
                                     let vis = if #is_set {
                                         ::mlua_gen::Visibility::Both
                                     } else {
                                         ::mlua_gen::Visibility::GetOnly
                                     };
-                                    let table = ::mlua_gen::build_indexed_proxy_leaf(lua, ctx, path, vis)?;
-                                    Ok(::mlua::Value::Table(table))
+                                    proxy(lua, ctx, path, vis)
                                 });
                             }
-                            if #is_set {
-                                reserved_fields.add_field_method_set(
-                                    #field_as_string,
-                                    |_, this, v: #field_ty| {
-                                        this.#field_ident = v;
-                                        #on_set_call
-                                        Ok(())
-                                    }
-                                );
-                            }
                         }
                         // Leaf — clone in/out as before.
-                        (false, false, false) => {
+                        None => {
                             if #is_get {
                                 reserved_fields
                                     .add_field_method_get(
                                         #field_as_string,
-                                        |_, this| Ok(this.#field_ident.clone())
-                                    );
-                            }
-
-                            if #is_set {
-                                reserved_fields
-                                    .add_field_method_set(
-                                        #field_as_string,
-                                        |_, this, v| {
-                                            this.#field_ident = v;
-                                            #on_set_call
-                                            Ok(())
-                                        }
+                                        |lua, this| #field_copy
                                     );
                             }
                         }
                     }
+                    // Whole-field replacement: outer.inner = { ... }
+                    if #is_set {
+                        reserved_fields.add_field_method_set(
+                            #field_as_string,
+                            #field_setter
+                        );
+                    }
                 );
 
 
@@ -256,46 +237,30 @@ pub(crate) fn user_data(
         Fields::Unnamed(_) => {
             // Tuple struct: `__index` / `__newindex` meta-functions on the
             // struct, with the same `(IS_MLUA_GENERATED, IS_INDEXABLE)` arms
-            // as named structs. Lua is 1-based; conversion is at codegen.
+            // as named structs. Lua keys start at `INDEX_BASE`.
             let set_field_strings: HashSet<&String> =
                 set_fields.iter().map(|f| &f.ident_string).collect();
             let get_arms = get_fields.iter().map(|field| {
                 let ident = &field.ident;
                 let ty = &field.ty;
+                let index = &field.index;
                 let zero_based: usize = field.ident_string.parse::<usize>().unwrap();
-                let lua_index: usize = zero_based + 1;
                 let is_set = set_field_strings.contains(&field.ident_string);
+                let field_copy = field_into_lua(&quote!(this.#ident.clone()), ty);
                 quote! {
-                    #lua_index => {
-                        let on_set: ::std::option::Option<::std::sync::Arc<dyn Fn() + ::std::marker::Send + ::std::marker::Sync>> = #on_set_resolver;
-                        let ctx = ::mlua_gen::make_resolver::<Self>(this.clone(), on_set);
+                    Some(#zero_based) => {
+                        let ctx = <Self as ::mlua_gen::MluaGenProject>::root_resolver(lua, this.clone())?;
                         let path = ::std::vec![::mlua_gen::PathStep::Tuple(#zero_based)];
                         let vis = if #is_set {
                             ::mlua_gen::Visibility::Both
                         } else {
                             ::mlua_gen::Visibility::GetOnly
                         };
-                        match (
-                            <#ty as ::mlua_gen::IsMluaGenerated>::IS_MLUA_GENERATED,
-                            <#ty as ::mlua_gen::CollectionProject>::IS_COLLECTION_OF_MLUA_GEN,
-                            <#ty as ::mlua_gen::IsIndexable>::IS_INDEXABLE,
-                        ) {
-                            (true, _, _) => {
-                                let table = <#ty as ::mlua_gen::MluaGenProjectMaybe>::maybe_build_proxy(lua, ctx, path, vis)?;
-                                ::mlua::Value::Table(table)
-                            },
-                            (_, true, _) => {
-                                let table = <#ty as ::mlua_gen::CollectionProject>::build_collection_proxy(lua, ctx, path, vis)?;
-                                ::mlua::Value::Table(table)
-                            },
-                            (_, _, true) => {
-                                let table = ::mlua_gen::build_indexed_proxy_leaf(lua, ctx, path, vis)?;
-                                ::mlua::Value::Table(table)
-                            },
-                            (false, false, false) => {
+                        match ::mlua_gen::probe!(#ty, #index => field_ops).proxy {
+                            Some(proxy) => proxy(lua, ctx, path, vis)?,
+                            None => {
                                 ::mlua_gen::with_parent::<Self, _>(&this, |this| {
-                                    use ::mlua::IntoLua;
-                                    this.#ident.clone().into_lua(lua)
+                                    #field_copy
                                 })?
                             },
                         }
@@ -306,23 +271,61 @@ pub(crate) fn user_data(
             let set_arms = set_fields.iter().map(|field| {
                 let ident = &field.ident;
                 let ty = &field.ty;
-                let lua_index: usize = field.ident_string.parse::<usize>().unwrap() + 1;
-                quote! {
-                    #lua_index => {
-                        let v = <#ty as ::mlua::FromLua>::from_lua(v, lua)?;
-                        ::mlua_gen::with_parent_mut::<Self, _>(&this, |this| {
-                            this.#ident = v;
-                            Ok(())
-                        })?;
-                        #on_set_call
-                    },
+                let zero_based: usize = field.ident_string.parse::<usize>().unwrap();
+                let step = lua_step(&field.ident_string);
+                if hooks.is_empty() {
+                    let write = field.constraints.write(
+                        &owner,
+                        &step,
+                        &quote!(this.#ident),
+                        ty,
+                        &quote!(v),
+                    );
+                    let from_lua = field_from_lua(&quote!(v), ty, &owner, &field.ident_string);
+                    quote! {
+                        Some(#zero_based) => {
+                            let v = #from_lua?;
+                            ::mlua_gen::with_parent_mut::<Self, _>(&this, |this| {
+                                #write
+                                Ok(())
+                            })?;
+                            #on_set_call
+                        },
+                    }
+                } else {
+                    let from_lua =
+                        field_from_lua(&quote!(new.clone()), ty, &owner, &field.ident_string);
+                    let assign = field.constraints.write(
+                        &owner,
+                        &step,
+                        &quote!(this.#ident),
+                        ty,
+                        &quote!(#from_lua?),
+                    );
+                    let write = hooks.around(
+                        &quote!(::mlua_gen::PathStep::Tuple(#zero_based)),
+                        &quote!(&this.#ident),
+                        ty,
+                        &assign,
+                        field.constraints.clamp.is_some(),
+                    );
+                    quote! {
+                        Some(#zero_based) => {
+                            let new = v;
+                            ::mlua_gen::with_parent_mut::<Self, _>(&this, |this| {
+                                #write
+                                Ok(())
+                            })?;
+                            #on_set_call
+                        },
+                    }
                 }
             });
 
+            let base = index_base_expr();
             let meta = quote! {
                 method_or_fns.add_meta_function("__index", |lua, (this, index): (::mlua::AnyUserData, usize)| {
-                    use ::mlua::IntoLua;
-                    Ok::<::mlua::Value, ::mlua::Error>(match index {
+                    Ok::<::mlua::Value, ::mlua::Error>(match index.checked_sub(#base) {
                         #(#get_arms)*
                         _ => return Err(::mlua::Error::runtime(
                             format!("Invalid index: {index}")
@@ -333,7 +336,7 @@ pub(crate) fn user_data(
                 method_or_fns.add_meta_function(
                     "__newindex",
                     |lua, (this, index, v): (::mlua::AnyUserData, usize, ::mlua::Value)| {
-                        match index {
+                        match index.checked_sub(#base) {
                             #(#set_arms)*
                             _ => return Err::<(), _>(::mlua::Error::runtime(
                                 format!("Invalid index: {index}")
@@ -346,7 +349,21 @@ pub(crate) fn user_data(
 
             (vec![], meta)
         },
-        Fields::Unit => (vec![], quote!()),
+        // The unit value is also the global of the type, holding its consts and built again
+        // when called
+        Fields::Unit => {
+            let consts = consts
+                .iter()
+                .map(|c| quote!(reserved_fields.add_field(stringify!(#c), Self::#c);))
+                .collect();
+            let meta = quote! {
+                method_or_fns.add_meta_function(
+                    ::mlua::MetaMethod::Call,
+                    |_, _: ::mlua::MultiValue| Ok(Self),
+                );
+            };
+            (consts, meta)
+        },
     };
 
     // Field
@@ -358,92 +375,47 @@ pub(crate) fn user_data(
         },
         // Code to `impl FromLua`
         match all_fields {
+            // `serde` builds the nested values too, without their `FromLua`
+            _ if serde => {
+                quote! {
+                    let mut this: Self = ::mlua_gen::from_table_serde!(lua, table)?;
+                    ::mlua_gen::MluaGenProject::check_fields(&mut this, lua)?;
+                    Ok(this)
+                }
+            },
             Fields::Named(fields) => {
-                let named_fields_constructor = fields
-                    .named
-                    .iter()
-                    .map(|named| named.ident.as_ref().expect("Is named"))
-                    .map(|field| {
-                        let stringified_field = field.to_token_stream().to_string();
-                        quote!(#field: table.get(#stringified_field)?)
-                    });
-
-                quote!(Self {
-                    #(#named_fields_constructor),*
-                })
+                let named_fields_constructor =
+                    named_fields_from_table(&owner, fields, &quote!(table));
+
+                checked_constructor(
+                    &owner,
+                    &fields.named,
+                    &quote!(Self { #(#named_fields_constructor),* }),
+                )
             },
             Fields::Unnamed(fields) => {
-                // For impl from lua
-                let impl_from_lua = repeat_with(|| {
-                    quote!(::mlua::FromLua::from_lua(
-                        sequence_value.next().ok_or_else(|| {
-                            ::mlua::Error::runtime("Not enough values in sequence table.")
-                        })??,
-                        lua,
-                    )?)
-                })
-                .take(fields.unnamed.len());
-
-                quote!(
-                    {
-                        let mut sequence_value: ::mlua::TableSequence<::mlua::Value> =
-                            table.sequence_values();
-
-                        Self(
-                            #(#impl_from_lua),*
-                        )
-                    }
-                )
+                // For impl from lua, keyed from `index_base` like the tuple fields
+                let base = index_base_expr();
+                let impl_from_lua = fields.unnamed.iter().enumerate().map(|(zb, field)| {
+                    let from_lua = field_from_lua(
+                        &quote!(table.get(#zb + #base)?),
+                        &field.ty,
+                        &owner,
+                        &zb.to_string(),
+                    );
+                    quote!(#from_lua?)
+                });
+
+                checked_constructor(&owner, &fields.unnamed, &quote!(Self(#(#impl_from_lua),*)))
             },
-            Fields::Unit => quote!(Self),
+            Fields::Unit => quote!(Ok(Self)),
         },
     );
 
 
 
     let (method_or_fns, method_or_fn_extra) = (
-        impls
-            .into_iter()
-            .map(|method_or_fn| {
-                let method_or_fn_ident = syn::Ident::new(&method_or_fn.name, Span::call_site());
-                let method_or_fn_string = method_or_fn.name;
-
-                let owned_fields: Vec<_> = method_or_fn
-                    .args
-                    .iter()
-                    .map(|arg| {
-                        Field {
-                            colon_token: None,
-                            attrs:       vec![],
-                            vis:         syn::Visibility::Inherited,
-                            mutability:  syn::FieldMutability::None,
-                            ident:       None,
-                            ty:          parse_str::<syn::Type>(arg).expect("Invalid type."),
-                        }
-                    })
-                    .collect();
-                let (argument, ty) = generate_tuple_access(owned_fields.iter());
-
-                let add_kind = match (method_or_fn.is_mut, method_or_fn.is_self) {
-                    (true, true) => quote!(add_method_mut),
-                    (false, true) => quote!(add_method),
-                    (true, false) => quote!(add_function_mut),
-                    (false, false) => quote!(add_function),
-                };
-
-                let (method_or_fn_caller, this) = if method_or_fn.is_self {
-                    (quote!(this.), quote!(this,))
-                } else {
-                    (quote!(Self::), quote!())
-                };
-
-                quote!(
-                    method_or_fns.#add_kind(#method_or_fn_string, |_, #this args: #ty| {
-                        Ok(#method_or_fn_caller #method_or_fn_ident(#argument))
-                    });
-                )
-            })
-            .collect::<Vec<TokenStream2>>(),
+        user_data_methods(impls),
         if let Some(method_or_fn) = custom_method_or_fn {
             quote!(#method_or_fn(method_or_fns))
         } else {
@@ -453,9 +425,30 @@ pub(crate) fn user_data(
 
 
 
+    let meta_methods = meta_methods();
+    // `pairs` lists the readable fields, reading them back through `__index`
+    let self_keys = self_keys(all_fields, &get_fields);
+    let meta_pairs = quote! {
+        let pairs = |lua: &::mlua::Lua, this: ::mlua::AnyUserData| {
+            let keys: ::mlua::Result<::mlua_gen::ProxyKeys> = #self_keys;
+            let iter = ::mlua_gen::pairs_iterator::<::mlua::AnyUserData>(lua, keys?)?;
+            Ok((iter, this, ::mlua::Value::Nil))
+        };
+        for metamethod in ::mlua_gen::PAIRS_METAMETHODS {
+            method_or_fns.add_meta_function(*metamethod, pairs);
+        }
+    };
     let non_typed_generics = remove_ty_from_generics(generics);
 
-    let project_impl = impl_project(name, generics, all_fields, &get_fields, &set_fields);
+    let project_impl = impl_project(
+        name,
+        generics,
+        all_fields,
+        &get_fields,
+        &set_fields,
+        index_base,
+        &root_resolver,
+    );
 
     quote! {
         #project_impl
@@ -464,7 +457,7 @@ pub(crate) fn user_data(
             fn from_lua(value: ::mlua::Value, lua: &::mlua::Lua) -> ::mlua::Result<#name #non_typed_generics> {
                 match value {
                     ::mlua::Value::Table(table) => {
-                        Ok(#struct_constructor)
+                        #struct_constructor
                     },
                     ::mlua::Value::UserData(user_data) => {
                         user_data.take()
@@ -484,6 +477,8 @@ pub(crate) fn user_data(
 
             fn add_methods<MluaUserDataMethods: ::mlua::UserDataMethods<Self>>(method_or_fns: &mut MluaUserDataMethods) {
                 #(#method_or_fns)*
+                #meta_methods
+                #meta_pairs
                 #meta_index
                 #method_or_fn_extra
             }
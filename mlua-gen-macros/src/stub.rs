@@ -0,0 +1,396 @@
+//! Codegen for `impl mlua_gen::LuaStub`. The annotations mirror what
+//! `user_data` and `builder` register: same fields, same `impl` entries,
+//! same variants.
+
+use {
+    crate::{
+        attr::{FieldAttributes, MethodOrFunction, MinimalField},
+        r#enum::ExposedVariant,
+        shared::remove_ty_from_generics,
+    },
+    proc_macro2::TokenStream as TokenStream2,
+    quote::quote,
+    syn::{
+        DataStruct,
+        Fields,
+        GenericArgument,
+        GenericParam,
+        Generics,
+        Ident,
+        PathArguments,
+        Type,
+        parse_str,
+    },
+};
+
+/// Function that impl the `mlua_gen::LuaStub` trait for a struct
+pub(crate) fn impl_stub_struct(
+    name: &Ident,
+    generics: &Generics,
+    ds: &DataStruct,
+    get_fields: &[MinimalField],
+    set_fields: &[MinimalField],
+    impls: &[MethodOrFunction],
+    consts: &[Ident],
+    index_base: usize,
+) -> TokenStream2 {
+    let params = generic_params(generics);
+    let class_name = name.to_string();
+
+    let mut class = vec![format!("---@class {class_name}")];
+    // The unit value is its own global, called to build it again
+    if ds.fields == Fields::Unit {
+        class.push(format!("---@overload {}", fun_type(None, &[], &class_name)));
+        class.extend(const_fields(consts));
+    }
+    let exposed = get_fields
+        .iter()
+        .chain(set_fields.iter().filter(|f| !get_fields.contains(f)));
+    for field in exposed {
+        let key = match ds.fields {
+            Fields::Unnamed(_) => format!("[{}]", tuple_lua_index(field, index_base)),
+            Fields::Named(_) | Fields::Unit => field.ident_string.clone(),
+        };
+        class.push(format!("---@field {key} {}", lua_type(&field.ty, &params)));
+    }
+    class.extend(method_fields(&class_name, impls, &params));
+
+    let functions: Vec<&MethodOrFunction> = impls.iter().filter(|fun| !fun.is_self).collect();
+    let constructor = match &ds.fields {
+        Fields::Named(named) => {
+            Some(fun_type(
+                None,
+                &[(
+                    "fields".to_owned(),
+                    table_type(
+                        named.named.iter().filter_map(|f| {
+                            // Errors are reported by `fields_from_visibility`
+                            let field_attributes = FieldAttributes::parse(f).unwrap_or_default();
+                            (!field_attributes.skip).then(|| {
+                                (
+                                    field_attributes.rename.unwrap_or_else(|| {
+                                        f.ident.as_ref().expect("Is named").to_string()
+                                    }),
+                                    &f.ty,
+                                )
+                            })
+                        }),
+                        &params,
+                    ),
+                )],
+                &class_name,
+            ))
+        },
+        Fields::Unnamed(unnamed) => {
+            Some(fun_type(
+                None,
+                &positional_args(unnamed.unnamed.iter().map(|f| &f.ty), &params),
+                &class_name,
+            ))
+        },
+        Fields::Unit => None,
+    };
+
+    let global = match constructor {
+        None => {
+            vec![
+                quote!("---@type "),
+                quote!(#class_name),
+                quote!("\n"),
+                quote!(name),
+                quote!(" = nil\n"),
+            ]
+        },
+        Some(constructor) => {
+            let overload = format!("---@overload {constructor}\n");
+            let consts = const_fields(consts)
+                .map(|line| line + "\n")
+                .collect::<String>();
+            let statics = static_functions(&functions, &params);
+            vec![
+                quote!("---@class "),
+                quote!(name),
+                quote!("Builder\n"),
+                quote!(#overload),
+                quote!(#consts),
+                quote!(name),
+                quote!(" = {}\n"),
+                statics,
+            ]
+        },
+    };
+
+    impl_trait(name, generics, &class, &global)
+}
+
+/// Function that impl the `mlua_gen::LuaStub` trait for an enum
+pub(crate) fn impl_stub_enum(
+    name: &Ident,
+    generics: &Generics,
+    variants: &[ExposedVariant],
+    impls: &[MethodOrFunction],
+    consts: &[Ident],
+    index_base: usize,
+) -> TokenStream2 {
+    let params = generic_params(generics);
+    let class_name = name.to_string();
+
+    let mut class = vec![format!("---@class {class_name}")];
+    let mut builder_fields = vec![];
+    for ExposedVariant { variant, get, .. } in variants {
+        let variant_name = variant.ident.to_string();
+        let accessor = variant_name.to_lowercase();
+        let (field_ty, builder_ty) = match &variant.fields {
+            Fields::Named(named) => {
+                let table =
+                    table_type(get.iter().map(|f| (f.ident_string.clone(), &f.ty)), &params);
+                let constructor = table_type(
+                    named
+                        .named
+                        .iter()
+                        .map(|f| (f.ident.as_ref().expect("Is named").to_string(), &f.ty)),
+                    &params,
+                );
+                let builder = fun_type(None, &[("fields".to_owned(), constructor)], &class_name);
+                (table, builder)
+            },
+            Fields::Unnamed(unnamed) => {
+                let tys: Vec<&Type> = unnamed.unnamed.iter().map(|f| &f.ty).collect();
+                let table = table_type(
+                    get.iter()
+                        .map(|f| (format!("[{}]", tuple_lua_index(f, index_base)), &f.ty)),
+                    &params,
+                );
+                let builder = fun_type(
+                    None,
+                    &positional_args(tys.iter().copied(), &params),
+                    &class_name,
+                );
+                (table, builder)
+            },
+            Fields::Unit => ("true".to_owned(), class_name.clone()),
+        };
+        class.push(format!("---@field {accessor} {field_ty}?"));
+        builder_fields.push(format!("---@field {variant_name} {builder_ty}\n"));
+    }
+    class.extend(method_fields(&class_name, impls, &params));
+    builder_fields.extend(const_fields(consts).map(|line| line + "\n"));
+
+    let builder_fields = builder_fields.concat();
+    let functions: Vec<&MethodOrFunction> = impls.iter().filter(|fun| !fun.is_self).collect();
+    let statics = static_functions(&functions, &params);
+    let global = vec![
+        quote!("---@class "),
+        quote!(name),
+        quote!("Builder\n"),
+        quote!(#builder_fields),
+        quote!(name),
+        quote!(" = {}\n"),
+        statics,
+    ];
+
+    impl_trait(name, generics, &class, &global)
+}
+
+fn impl_trait(
+    name: &Ident,
+    generics: &Generics,
+    class: &[String],
+    global: &[TokenStream2],
+) -> TokenStream2 {
+    let non_typed_generics = remove_ty_from_generics(generics);
+    let class = class
+        .iter()
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    let lua_name = name.to_string();
+
+    quote! {
+        impl #generics ::mlua_gen::LuaStub for #name #non_typed_generics {
+            const LUA_NAME: &'static str = #lua_name;
+
+            fn lua_class_stub() -> ::std::string::String {
+                ::std::string::String::from(#class)
+            }
+
+            fn lua_global_stub(name: &str) -> ::std::string::String {
+                [#(#global),*].concat()
+            }
+        }
+    }
+}
+
+/// `---@field name fun(...)` for every `impl = [...]` entry registered on the userdata.
+fn method_fields(class_name: &str, impls: &[MethodOrFunction], params: &[String]) -> Vec<String> {
+    impls
+        .iter()
+        .map(|fun| {
+            let this = fun.is_self.then_some(class_name);
+            format!(
+                "---@field {} {}",
+                fun.name,
+                fun_type(this, &fun_args(fun, params), "any")
+            )
+        })
+        .collect()
+}
+
+/// `---@field NAME any` for every `consts = [...]` entry, whose type the macro can't see.
+fn const_fields(consts: &[Ident]) -> impl Iterator<Item = String> + '_ {
+    consts.iter().map(|c| format!("---@field {c} any"))
+}
+
+/// `---@param` / `---@return` declarations for the functions stored in the
+/// global table (`new()`, `default()`, ...).
+fn static_functions(functions: &[&MethodOrFunction], params: &[String]) -> TokenStream2 {
+    let pieces = functions.iter().map(|fun| {
+        let args = fun_args(fun, params);
+        let annotations = args
+            .iter()
+            .map(|(arg, ty)| format!("---@param {arg} {ty}\n"))
+            .collect::<String>();
+        let annotations = format!("\n{annotations}---@return any\nfunction ");
+        let signature = format!(
+            ".{}({}) end\n",
+            fun.name,
+            args.iter()
+                .map(|(arg, _)| arg.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        quote!(#annotations, name, #signature)
+    });
+
+    quote!(#(#pieces),*)
+}
+
+fn fun_args(fun: &MethodOrFunction, params: &[String]) -> Vec<(String, String)> {
+    let tys: Vec<Type> = fun
+        .args
+        .iter()
+        .map(|arg| parse_str::<Type>(arg).expect("Invalid type."))
+        .collect();
+    positional_args(tys.iter(), params)
+}
+
+fn positional_args<'l, I: Iterator<Item = &'l Type>>(
+    tys: I,
+    params: &[String],
+) -> Vec<(String, String)> {
+    tys.enumerate()
+        .map(|(idx, ty)| (format!("arg{}", idx + 1), lua_type(ty, params)))
+        .collect()
+}
+
+fn fun_type(this: Option<&str>, args: &[(String, String)], ret: &str) -> String {
+    let args = this
+        .map(|this| format!("self: {this}"))
+        .into_iter()
+        .chain(args.iter().map(|(arg, ty)| format!("{arg}: {ty}")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("fun({args}): {ret}")
+}
+
+fn table_type<'l, I: Iterator<Item = (String, &'l Type)>>(fields: I, params: &[String]) -> String {
+    let fields = fields
+        .map(|(key, ty)| format!("{key}: {}", lua_type(ty, params)))
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        "{}".to_owned()
+    } else {
+        format!("{{ {} }}", fields.join(", "))
+    }
+}
+
+/// Lua key of a tuple field, `index_base` being the one of its type.
+fn tuple_lua_index(field: &MinimalField, index_base: usize) -> usize {
+    field
+        .ident_string
+        .parse::<usize>()
+        .expect("tuple field must be numeric") +
+        index_base
+}
+
+fn generic_params(generics: &Generics) -> Vec<String> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| {
+            match param {
+                GenericParam::Type(ty) => Some(ty.ident.to_string()),
+                GenericParam::Lifetime(_) | GenericParam::Const(_) => None,
+            }
+        })
+        .collect()
+}
+
+/// Best-effort mapping from a Rust type to its LuaLS name. Anything that
+/// isn't a known std type is assumed to be another `#[mlua_gen]` class.
+fn lua_type(ty: &Type, params: &[String]) -> String {
+    match ty {
+        Type::Reference(reference) => lua_type(&reference.elem, params),
+        Type::Paren(paren) => lua_type(&paren.elem, params),
+        Type::Group(group) => lua_type(&group.elem, params),
+        Type::Array(array) => array_of(&lua_type(&array.elem, params)),
+        Type::Slice(slice) => array_of(&lua_type(&slice.elem, params)),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "nil".to_owned(),
+        Type::Path(path) if path.qself.is_none() => {
+            let Some(segment) = path.path.segments.last() else {
+                return "any".to_owned();
+            };
+            let ident = segment.ident.to_string();
+            let args: Vec<String> = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => {
+                    args.args
+                        .iter()
+                        .filter_map(|arg| {
+                            match arg {
+                                GenericArgument::Type(ty) => Some(lua_type(ty, params)),
+                                _ => None,
+                            }
+                        })
+                        .collect()
+                },
+                PathArguments::None | PathArguments::Parenthesized(_) => vec![],
+            };
+
+            match (ident.as_str(), args.as_slice()) {
+                _ if params.contains(&ident) => "any".to_owned(),
+                (
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" |
+                    "u64" | "u128" | "usize",
+                    [],
+                ) => "integer".to_owned(),
+                ("f32" | "f64", []) => "number".to_owned(),
+                ("bool", []) => "boolean".to_owned(),
+                ("String" | "str" | "char" | "LuaString", []) => "string".to_owned(),
+                ("Table", []) => "table".to_owned(),
+                ("Function", []) => "function".to_owned(),
+                ("Value", []) => "any".to_owned(),
+                ("Option", [inner]) => format!("{}?", wrap_compound(inner)),
+                // `SmallVec<[T; N]>`: the array argument is already `T[]`
+                ("Box" | "Rc" | "Arc" | "SmallVec", [inner]) => inner.clone(),
+                ("Vec" | "VecDeque" | "LinkedList", [inner]) => array_of(inner),
+                ("HashSet" | "BTreeSet", [inner]) => format!("table<{inner}, true>"),
+                ("HashMap" | "BTreeMap" | "IndexMap", [key, value, ..]) => {
+                    format!("table<{key}, {value}>")
+                },
+                _ => ident,
+            }
+        },
+        _ => "any".to_owned(),
+    }
+}
+
+fn array_of(inner: &str) -> String {
+    format!("{}[]", wrap_compound(inner))
+}
+
+fn wrap_compound(inner: &str) -> String {
+    if inner.contains(['?', '|', ' ']) {
+        format!("({inner})")
+    } else {
+        inner.to_owned()
+    }
+}
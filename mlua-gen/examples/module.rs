@@ -0,0 +1,27 @@
+//! Native Lua module, loaded with `require("game")` once built as `game.so`:
+//!
+//! ```sh
+//! cargo build -p mlua-gen --example module --no-default-features --features lua54,module
+//! ```
+
+use mlua_gen::mlua_gen;
+
+#[mlua_gen(get = *, set = *)]
+pub struct Player {
+    pub name: String,
+}
+
+#[mlua_gen]
+pub enum Faction {
+    Red,
+    Blue,
+}
+
+mod units {
+    #[mlua_gen::mlua_gen(get = *)]
+    pub struct Enemy {
+        pub hp: i32,
+    }
+}
+
+mlua_gen::lua_module!(game, [Player, Faction, units::Enemy]);
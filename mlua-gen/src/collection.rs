@@ -0,0 +1,305 @@
+//! Methods of collection proxies (`push`, `remove`, `keys`, ...). Writes are
+//! walked down the path with `MluaGenProject::project_call`, reads go
+//! through the `get`/`keys` walkers.
+
+use {
+    crate::proxy::{LuaKey, PathStep, ProxyKeys, Resolver, Visibility, within_elem},
+    mlua::{FromLua, FromLuaMulti, Function, IntoLua, Lua, MultiValue, Value},
+    std::{
+        any::type_name,
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+        hash::Hash,
+        rc::Rc,
+        sync::Arc,
+    },
+};
+
+/// Method of a collection proxy writing to the collection.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionMethod {
+    Push,
+    Pop,
+    Insert,
+    Remove,
+    Clear,
+}
+
+impl CollectionMethod {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "push" => Self::Push,
+            "pop" => Self::Pop,
+            "insert" => Self::Insert,
+            "remove" => Self::Remove,
+            "clear" => Self::Clear,
+            _ => return None,
+        })
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Push => "push",
+            Self::Pop => "pop",
+            Self::Insert => "insert",
+            Self::Remove => "remove",
+            Self::Clear => "clear",
+        }
+    }
+}
+
+pub(crate) fn unsupported_method<T: ?Sized>(method: CollectionMethod) -> mlua::Error {
+    mlua::Error::runtime(format!(
+        "`{}` has no `{}` method",
+        type_name::<T>(),
+        method.name()
+    ))
+}
+
+/// 0-based index of the Lua `index`, which has to be in `base..base + len`.
+fn rust_index(index: usize, base: usize, len: usize) -> mlua::Result<usize> {
+    if (base..base + len).contains(&index) {
+        Ok(index - base)
+    } else {
+        Err(mlua::Error::runtime(format!(
+            "index {index} out of bounds {base}..{}",
+            base + len
+        )))
+    }
+}
+
+/// Applies a [`CollectionMethod`]. Implemented for the growable sequences
+/// and the maps; the default method errors, for the other collections.
+///
+/// On a map proxy, an entry shadows the method of the same name: `m.keys` is
+/// the entry `"keys"` when the map has one, and the `keys` method otherwise.
+/// `m:contains("keys")` tells them apart.
+#[doc(hidden)]
+pub trait CollectionMethods {
+    fn call_method(
+        &mut self,
+        _lua: &Lua,
+        _base: usize,
+        method: CollectionMethod,
+        _args: MultiValue,
+    ) -> mlua::Result<Value> {
+        Err(unsupported_method::<Self>(method))
+    }
+}
+
+// Fixed length sequences and sets have no methods
+impl<T, const N: usize> CollectionMethods for [T; N] {}
+impl<T> CollectionMethods for Box<[T]> {}
+impl<T> CollectionMethods for Rc<[T]> {}
+impl<T> CollectionMethods for Arc<[T]> {}
+impl<T> CollectionMethods for HashSet<T> {}
+impl<T> CollectionMethods for BTreeSet<T> {}
+
+/// `CollectionMethods` of a growable sequence, `$push`/`$pop` working at its end.
+macro_rules! sequence_methods {
+    ([$($generics:tt)*] $sequence:ty, $elem:ty, $push:ident, $pop:ident) => {
+        impl<$($generics)*> CollectionMethods for $sequence
+        where
+            $elem: FromLua + IntoLua,
+        {
+            fn call_method(
+                &mut self,
+                lua: &Lua,
+                base: usize,
+                method: CollectionMethod,
+                args: MultiValue,
+            ) -> mlua::Result<Value> {
+                match method {
+                    CollectionMethod::Push => {
+                        let value = <$elem>::from_lua_multi(args, lua)
+                            .map_err(|err| within_elem(err, base + self.len()))?;
+                        self.$push(value);
+                        Ok(Value::Nil)
+                    },
+                    CollectionMethod::Pop => self.$pop().into_lua(lua),
+                    CollectionMethod::Insert => {
+                        let (index, value) = <(usize, Value)>::from_lua_multi(args, lua)?;
+                        let idx = rust_index(index, base, self.len() + 1)?;
+                        let value =
+                            <$elem>::from_lua(value, lua).map_err(|err| within_elem(err, index))?;
+                        self.insert(idx, value);
+                        Ok(Value::Nil)
+                    },
+                    CollectionMethod::Remove => {
+                        let idx = rust_index(usize::from_lua_multi(args, lua)?, base, self.len())?;
+                        self.remove(idx).into_lua(lua)
+                    },
+                    CollectionMethod::Clear => {
+                        self.clear();
+                        Ok(Value::Nil)
+                    },
+                }
+            }
+        }
+    };
+}
+
+sequence_methods!([T] Vec<T>, T, push, pop);
+sequence_methods!([T] VecDeque<T>, T, push_back, pop_back);
+#[cfg(feature = "smallvec")]
+sequence_methods!([A: smallvec::Array] smallvec::SmallVec<A>, A::Item, push, pop);
+
+/// `CollectionMethods` of a map, `$remove` removing an entry.
+macro_rules! map_methods {
+    ([$($generics:tt)*] $map:ty, $remove:ident, $($key:tt)+) => {
+        impl<$($generics)*> CollectionMethods for $map
+        where
+            K: $($key)+ + FromLua,
+            V: FromLua + IntoLua,
+        {
+            fn call_method(
+                &mut self,
+                lua: &Lua,
+                _base: usize,
+                method: CollectionMethod,
+                args: MultiValue,
+            ) -> mlua::Result<Value> {
+                match method {
+                    CollectionMethod::Insert => {
+                        let (key, value) = <(Value, Value)>::from_lua_multi(args, lua)?;
+                        let value =
+                            V::from_lua(value, lua).map_err(|err| within_elem(err, LuaKey(&key)))?;
+                        self.insert(K::from_lua(key, lua)?, value).into_lua(lua)
+                    },
+                    CollectionMethod::Remove => {
+                        self.$remove(&K::from_lua_multi(args, lua)?).into_lua(lua)
+                    },
+                    CollectionMethod::Clear => {
+                        self.clear();
+                        Ok(Value::Nil)
+                    },
+                    CollectionMethod::Push | CollectionMethod::Pop => {
+                        Err(unsupported_method::<Self>(method))
+                    },
+                }
+            }
+        }
+    };
+}
+
+map_methods!([K, V] HashMap<K, V>, remove, Eq + Hash);
+map_methods!([K, V] BTreeMap<K, V>, remove, Ord);
+// `shift_remove` keeps the order of the other entries
+#[cfg(feature = "indexmap")]
+map_methods!(
+    [K, V, S: std::hash::BuildHasher] indexmap::IndexMap<K, V, S>,
+    shift_remove,
+    Eq + Hash
+);
+
+/// The element a [`CollectionMethod`] call writes, as reported to the
+/// `validate`/`on_change` hooks: `push`/`insert` write their value,
+/// `pop`/`remove` write `nil`, and `clear` writes an empty table to the
+/// collection itself. `None` when nothing is written.
+pub(crate) fn written_element(
+    lua: &Lua,
+    ctx: &Resolver,
+    steps: &[PathStep],
+    method: CollectionMethod,
+    args: &MultiValue,
+) -> mlua::Result<Option<(Vec<PathStep>, Value)>> {
+    let arg = |position: usize| args.get(position).cloned().unwrap_or(Value::Nil);
+    let (key, value) = match method {
+        CollectionMethod::Push | CollectionMethod::Pop => {
+            let ProxyKeys::Sequence { base, len } = (ctx.keys)(lua, steps)? else {
+                return Ok(None);
+            };
+            match (method, len) {
+                (CollectionMethod::Push, _) => ((base + len).into_lua(lua)?, arg(0)),
+                (_, 0) => return Ok(None),
+                _ => ((base + len - 1).into_lua(lua)?, Value::Nil),
+            }
+        },
+        CollectionMethod::Insert => (arg(0), arg(1)),
+        CollectionMethod::Remove => (arg(0), Value::Nil),
+        CollectionMethod::Clear => {
+            return Ok(Some((steps.to_vec(), Value::Table(lua.create_table()?))));
+        },
+    };
+    let mut path = steps.to_vec();
+    path.push(PathStep::Index(key));
+    Ok(Some((path, value)))
+}
+
+/// Whether the collection at `path` contains `needle`: an element for
+/// sequences, compared with Lua's `==`, a key for maps.
+fn contains(lua: &Lua, ctx: &Resolver, path: &[PathStep], needle: &Value) -> mlua::Result<bool> {
+    let keys = (ctx.keys)(lua, path)?;
+    if !matches!(keys, ProxyKeys::Sequence { .. }) {
+        return Ok(keys.contains(needle));
+    }
+    for position in 0..keys.len() {
+        let Some(key) = keys.key(lua, position)? else {
+            continue;
+        };
+        let mut p = path.to_vec();
+        p.push(PathStep::Index(key));
+        if (ctx.get)(lua, &p)?.equals(needle)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Method `key` of the collection proxy at `path`, `None` when `key` isn't a
+/// method name or is an entry of the map.
+#[doc(hidden)]
+pub fn collection_method(
+    lua: &Lua,
+    ctx: &Resolver,
+    path: &[PathStep],
+    key: &Value,
+    vis: Visibility,
+) -> mlua::Result<Option<Function>> {
+    let Some(name) = key.as_string().and_then(|name| name.to_str().ok()) else {
+        return Ok(None);
+    };
+    let read_only = matches!(&*name, "contains" | "keys");
+    let method = CollectionMethod::from_name(&name);
+    if !read_only && method.is_none() {
+        return Ok(None);
+    }
+    let keys = (ctx.keys)(lua, path)?;
+    if !matches!(keys, ProxyKeys::Sequence { .. }) && keys.contains(key) {
+        return Ok(None);
+    }
+
+    let ctx = ctx.clone();
+    let path = path.to_vec();
+    let function = match method {
+        Some(method) if vis == Visibility::Both => {
+            lua.create_function(move |lua, (_, args): (Value, MultiValue)| {
+                ctx.call_and_fire(lua, &path, method, args)
+            })?
+        },
+        Some(method) => {
+            return Err(mlua::Error::runtime(format!(
+                "collection is read-only: no `{}` method",
+                method.name()
+            )));
+        },
+        None if &*name == "contains" => {
+            lua.create_function(move |lua, (_, needle): (Value, Value)| {
+                contains(lua, &ctx, &path, &needle)
+            })?
+        },
+        None => {
+            lua.create_function(move |lua, _: Value| {
+                let keys = (ctx.keys)(lua, &path)?;
+                let table = lua.create_table_with_capacity(keys.len(), 0)?;
+                for position in 0..keys.len() {
+                    if let Some(key) = keys.key(lua, position)? {
+                        table.raw_push(key)?;
+                    }
+                }
+                Ok(table)
+            })?
+        },
+    };
+    Ok(Some(function))
+}
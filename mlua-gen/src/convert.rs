@@ -0,0 +1,58 @@
+//! Lua conversions of field values: `IntoLua`/`FromLua` when implemented, else the collections
+//! `mlua` has no conversion for, which go through plain Lua tables.
+
+use {
+    mlua::{FromLua, IntoLua, Lua, Value},
+    std::collections::VecDeque,
+};
+
+/// Collections converted from and to plain Lua tables: `VecDeque`, and `SmallVec`/`IndexMap`
+/// behind the features of the same name. Generated code converts fields with
+/// `probe!(T => field_into_lua)` and `probe!(T => field_from_lua)`, which go through it for the
+/// types without `IntoLua`/`FromLua`.
+#[doc(hidden)]
+pub trait LuaCollection: Sized {
+    fn collection_into_lua(self, lua: &Lua) -> mlua::Result<Value>;
+    fn collection_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self>;
+}
+
+impl<T: IntoLua + FromLua> LuaCollection for VecDeque<T> {
+    fn collection_into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        Vec::from(self).into_lua(lua)
+    }
+
+    fn collection_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self> {
+        Vec::from_lua(value, lua).map(Self::from)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A> LuaCollection for smallvec::SmallVec<A>
+where
+    A: smallvec::Array<Item: IntoLua + FromLua>,
+{
+    fn collection_into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        self.into_vec().into_lua(lua)
+    }
+
+    fn collection_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self> {
+        Vec::from_lua(value, lua).map(Self::from_vec)
+    }
+}
+
+/// Lua tables have no order: entries read from a table are inserted in the order of `pairs`.
+#[cfg(feature = "indexmap")]
+impl<K, V, S> LuaCollection for indexmap::IndexMap<K, V, S>
+where
+    K: Eq + std::hash::Hash + IntoLua + FromLua,
+    V: IntoLua + FromLua,
+    S: std::hash::BuildHasher + Default,
+{
+    fn collection_into_lua(self, lua: &Lua) -> mlua::Result<Value> {
+        lua.create_table_from(self).map(Value::Table)
+    }
+
+    fn collection_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self> {
+        mlua::Table::from_lua(value, lua)?.pairs::<K, V>().collect()
+    }
+}
@@ -1,54 +1,187 @@
-#![allow(incomplete_features, reason = "This is the only way to make it work")]
-#![feature(never_type)]
-#![feature(specialization)]
+#![cfg_attr(
+    feature = "nightly",
+    allow(incomplete_features, reason = "`specialization` is incomplete")
+)]
+#![cfg_attr(feature = "nightly", feature(specialization))]
 
+mod collection;
+mod convert;
+mod module;
+mod operators;
 mod proxy;
+#[cfg(feature = "nightly")]
+mod specialize;
+mod stub;
 mod trait_helpers;
+#[cfg(feature = "nightly")]
+pub use specialize::*;
+pub use {
+    collection::*,
+    convert::*,
+    mlua_gen_macros::mlua_gen,
+    module::*,
+    operators::*,
+    proxy::*,
+    stub::*,
+    trait_helpers::*,
+};
 use {
     mlua::{AnyUserData, FromLua, IntoLua},
     std::{
-        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+        collections::{BTreeMap, HashMap, VecDeque},
         hash::Hash,
-        sync::{Arc, Mutex},
+        sync::{Arc, Mutex, RwLock},
     },
 };
-pub use {mlua_gen_macros::mlua_gen, proxy::*, trait_helpers::*};
 
-/// Borrow a parent `AnyUserData` as `&T`, falling back to
-/// `&Arc<Mutex<T>>` when the parent was injected pre-wrapped.
+// A module is loaded into a Lua state owned by the host, which can't be made `Send`: mlua refuses
+// its `send` feature there, and so do we, before the generated code fails on it.
+#[cfg(all(feature = "module", feature = "send"))]
+compile_error!(
+    "mlua-gen: the `module` feature can't be used with `send`, which is on by default. Depend on \
+     mlua-gen with `default-features = false` and pick a Lua version, e.g. `features = [\"lua54\", \
+     \"module\"]`."
+);
+
+/// Borrow a parent `AnyUserData` as `&T`, falling back to the shared roots
+/// it may have been injected as: `Arc<Mutex<T>>`, `Arc<RwLock<T>>` (read
+/// lock), and their `parking_lot`/`tokio` counterparts behind the features of
+/// the same name. mlua has no userdata for the tokio ones, which Lua reaches
+/// through [`root_proxy`].
 #[doc(hidden)]
 pub fn with_parent<T: 'static, R>(
     ud: &AnyUserData,
     f: impl FnOnce(&T) -> mlua::Result<R>,
 ) -> mlua::Result<R> {
-    match ud.borrow::<T>() {
-        Ok(this) => f(&this),
-        Err(_) => {
-            let arc = ud.borrow::<Arc<Mutex<T>>>()?;
-            let guard = arc
-                .lock()
-                .map_err(|_| mlua::Error::runtime("parent mutex poisoned"))?;
-            f(&guard)
-        },
+    if let Ok(this) = ud.borrow::<T>() {
+        return f(&this);
+    }
+    if let Ok(arc) = ud.borrow::<Arc<Mutex<T>>>() {
+        let guard = arc
+            .lock()
+            .map_err(|_| mlua::Error::runtime("parent mutex poisoned"))?;
+        return f(&guard);
     }
+    if let Ok(arc) = ud.borrow::<Arc<RwLock<T>>>() {
+        let guard = arc
+            .read()
+            .map_err(|_| mlua::Error::runtime("parent lock poisoned"))?;
+        return f(&guard);
+    }
+    #[cfg(feature = "parking_lot")]
+    {
+        if let Ok(arc) = ud.borrow::<Arc<parking_lot::Mutex<T>>>() {
+            return f(&arc.lock());
+        }
+        if let Ok(arc) = ud.borrow::<Arc<parking_lot::RwLock<T>>>() {
+            return f(&arc.read());
+        }
+    }
+    #[cfg(feature = "tokio")]
+    {
+        if let Ok(arc) = ud.borrow::<Arc<tokio::sync::Mutex<T>>>() {
+            let guard = tokio_guard(|| arc.try_lock(), || arc.blocking_lock())?;
+            return f(&guard);
+        }
+        if let Ok(arc) = ud.borrow::<Arc<tokio::sync::RwLock<T>>>() {
+            let guard = tokio_guard(|| arc.try_read(), || arc.blocking_read())?;
+            return f(&guard);
+        }
+    }
+    Err(mlua::Error::UserDataTypeMismatch)
 }
 
-/// Mutable [`with_parent`].
+/// Mutable [`with_parent`], taking write locks.
 #[doc(hidden)]
 pub fn with_parent_mut<T: 'static, R>(
     ud: &AnyUserData,
     f: impl FnOnce(&mut T) -> mlua::Result<R>,
 ) -> mlua::Result<R> {
-    match ud.borrow_mut::<T>() {
-        Ok(mut this) => f(&mut this),
-        Err(_) => {
-            let arc = ud.borrow::<Arc<Mutex<T>>>()?;
-            let mut guard = arc
-                .lock()
-                .map_err(|_| mlua::Error::runtime("parent mutex poisoned"))?;
-            f(&mut guard)
-        },
+    if let Ok(mut this) = ud.borrow_mut::<T>() {
+        return f(&mut this);
+    }
+    if let Ok(arc) = ud.borrow::<Arc<Mutex<T>>>() {
+        let mut guard = arc
+            .lock()
+            .map_err(|_| mlua::Error::runtime("parent mutex poisoned"))?;
+        return f(&mut guard);
+    }
+    if let Ok(arc) = ud.borrow::<Arc<RwLock<T>>>() {
+        let mut guard = arc
+            .write()
+            .map_err(|_| mlua::Error::runtime("parent lock poisoned"))?;
+        return f(&mut guard);
+    }
+    #[cfg(feature = "parking_lot")]
+    {
+        if let Ok(arc) = ud.borrow::<Arc<parking_lot::Mutex<T>>>() {
+            return f(&mut arc.lock());
+        }
+        if let Ok(arc) = ud.borrow::<Arc<parking_lot::RwLock<T>>>() {
+            return f(&mut arc.write());
+        }
     }
+    #[cfg(feature = "tokio")]
+    {
+        if let Ok(arc) = ud.borrow::<Arc<tokio::sync::Mutex<T>>>() {
+            let mut guard = tokio_guard(|| arc.try_lock(), || arc.blocking_lock())?;
+            return f(&mut guard);
+        }
+        if let Ok(arc) = ud.borrow::<Arc<tokio::sync::RwLock<T>>>() {
+            let mut guard = tokio_guard(|| arc.try_write(), || arc.blocking_write())?;
+            return f(&mut guard);
+        }
+    }
+    Err(mlua::Error::UserDataTypeMismatch)
+}
+
+/// Locks a tokio root. Lua callbacks are synchronous: the lock is waited for outside of a tokio
+/// runtime, but only tried inside one, where blocking would stall the runtime, raising an error
+/// when it is held.
+#[cfg(feature = "tokio")]
+fn tokio_guard<G, E, Try, Block>(try_lock: Try, block: Block) -> mlua::Result<G>
+where
+    Try: FnOnce() -> Result<G, E>,
+    Block: FnOnce() -> G,
+{
+    if tokio::runtime::Handle::try_current().is_err() {
+        return Ok(block());
+    }
+    try_lock().map_err(|_| {
+        mlua::Error::runtime("parent lock is held, and can't be waited for inside a tokio runtime")
+    })
+}
+
+/// Builds a `T` from a plain Lua table through `serde`, for `#[mlua_gen(serde)]` types. This is
+/// what makes `#[serde(default)]`, `#[serde(rename)]`, `#[serde(flatten)]`, etc. apply.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub fn from_table_serde<T: serde::de::DeserializeOwned>(
+    lua: &mlua::Lua,
+    table: mlua::Table,
+) -> mlua::Result<T> {
+    mlua::LuaSerdeExt::from_value(lua, mlua::Value::Table(table))
+}
+
+/// `from_table_serde!(lua, table)`: what `#[mlua_gen(serde)]` types call, [`from_table_serde`]
+/// with the `serde` feature, a compilation error without it.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! from_table_serde {
+    ($lua:expr, $table:expr) => {
+        $crate::from_table_serde($lua, $table)
+    };
+}
+
+/// Same, without the `serde` feature.
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! from_table_serde {
+    ($lua:expr, $table:expr) => {
+        ::core::compile_error!("#[mlua_gen(serde)] needs the `serde` feature of mlua-gen")
+    };
 }
 
 pub trait LuaBuilder<R: IntoLua + FromLua, Lua, E, Table> {
@@ -70,11 +203,13 @@ pub trait LuaBuilder<R: IntoLua + FromLua, Lua, E, Table> {
     /// ```
     fn lua_builder(lua: &Lua) -> Result<R, E>;
 
-    /// Creates the the constructor functions for a struct or enum.
+    /// Creates the the constructor functions for a struct or enum, along with its
+    /// `consts = [...]`.
     ///
     /// ### Note
     ///
-    /// When used with [LuaBuilder::to_globals], it will register structs functions under `MyStruct_`
+    /// When used with [LuaBuilder::to_globals], they end up in the same table as the constructor
+    /// of the struct, or as the variants of the enum.
     fn lua_fn_builder(lua: &Lua) -> Result<Option<Table>, E>;
 
     /// Add a struct or enum to the global values of Lua.
@@ -82,17 +217,41 @@ pub trait LuaBuilder<R: IntoLua + FromLua, Lua, E, Table> {
     /// This function creates both enum&struct declaration but also function declaration and
     /// associated data like, field/variant access, methods etc.
     ///
-    /// For structs (and not enums) function declaration (like `new`, `default`, etc.), note that
-    /// you'll need to use `MyStruct_` when declared as `MyStruct`.
+    /// Each type gets a single global. For enums, it is the table of the variants, of the
+    /// functions (like `new`, `default`, etc.) and of the consts. For structs with functions or
+    /// consts, it is their table, building the struct when called. A unit struct stays its value,
+    /// holding its functions and consts too, and called to build it again:
+    /// ```lua
+    /// local named = MyNamedStruct { key = value }
+    /// local default = MyNamedStruct.default()
+    /// local max = MyNamedStruct.MAX
+    /// local unit = MyUnitStruct
+    /// local zero = MyUnitStruct.zero()
+    /// local again = MyUnitStruct()
+    /// ```
     fn to_globals(lua: &Lua) -> Result<(), E>;
 
     /// Same as [LuaBuilder::to_globals] but it will register the struct/enum with a custom name
     /// instead of the default Rust name.
+    ///
+    /// A dotted name like `game.entities.Player` registers it in nested tables, creating the
+    /// missing ones.
     fn to_globals_as<S: AsRef<str>>(lua: &Lua, s: S) -> Result<(), E>;
+
+    /// Same as [LuaBuilder::to_globals] but in `table` instead of the globals, like the
+    /// environment of a sandbox or the table of a [`Module`].
+    fn to_table(lua: &Lua, table: &Table) -> Result<(), E>;
+
+    /// Same as [LuaBuilder::to_globals_as] but in `table` instead of the globals.
+    fn to_table_as<S: AsRef<str>>(lua: &Lua, table: &Table, s: S) -> Result<(), E>;
 }
 
+/// Registers types in the globals, or in a table with `to_lua!(lua => table, A, B)`.
 #[macro_export]
 macro_rules! to_lua {
+    ($lua:ident => $table:expr, $($struct_or_enum:ident),*) => {
+        $($struct_or_enum::to_table(&$lua, &$table)?);*
+    };
     ($lua:ident, $($struct_or_enum:ident),*) => {
         $($struct_or_enum::to_globals(&$lua)?);*
     };
@@ -102,63 +261,111 @@ macro_rules! to_lua {
 pub trait NewIndex {
     type Key;
     type Item;
-    fn new_index(&mut self, index: Self::Key, item: Self::Item);
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()>;
 }
 
-impl<T: Default> NewIndex for Vec<T> {
-    type Item = T;
-    type Key = usize;
+/// `NewIndex` of a growable sequence. Writing just past the end appends, further out errors
+/// like the sequences of `mlua_gen` elements.
+macro_rules! sequence_new_index {
+    ([$($generics:tt)*] $sequence:ty, $elem:ty, $push:ident) => {
+        impl<$($generics)*> NewIndex for $sequence {
+            type Item = $elem;
+            type Key = usize;
 
-    fn new_index(&mut self, index: Self::Key, item: Self::Item) {
-        if index >= self.len() {
-            self.resize_with(index + 1, Default::default);
+            fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+                let len = self.len();
+                if index == len {
+                    self.$push(item);
+                    return Ok(());
+                }
+                let slot = self.get_mut(index).ok_or_else(|| {
+                    mlua::Error::runtime(format!(
+                        "index out of bounds of a sequence of length {len}, only writing just past \
+                         the end appends"
+                    ))
+                })?;
+                *slot = item;
+                Ok(())
+            }
         }
+    };
+}
+
+sequence_new_index!([T] Vec<T>, T, push);
+sequence_new_index!([T] VecDeque<T>, T, push_back);
+#[cfg(feature = "smallvec")]
+sequence_new_index!([A: smallvec::Array] smallvec::SmallVec<A>, A::Item, push);
+
+/// Writes to a sequence of fixed length, which can't grow.
+fn fixed_new_index<T>(sequence: &mut [T], index: usize, item: T) -> mlua::Result<()> {
+    let len = sequence.len();
+    let slot = sequence.get_mut(index).ok_or_else(|| {
+        mlua::Error::runtime(format!(
+            "index out of bounds of a sequence of fixed length {len}"
+        ))
+    })?;
+    *slot = item;
+    Ok(())
+}
 
-        self[index] = item;
+impl<T, const N: usize> NewIndex for [T; N] {
+    type Item = T;
+    type Key = usize;
+
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+        fixed_new_index(self, index, item)
     }
 }
 
-impl<K: Eq + Hash, V> NewIndex for HashMap<K, V> {
-    type Item = V;
-    type Key = K;
+impl<T> NewIndex for Box<[T]> {
+    type Item = T;
+    type Key = usize;
 
-    fn new_index(&mut self, index: Self::Key, item: Self::Item) {
-        self.insert(index, item);
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+        fixed_new_index(self, index, item)
     }
 }
 
-impl<K: Ord, V> NewIndex for BTreeMap<K, V> {
-    type Item = V;
+/// Assigning `nil` removes the entry.
+impl<K: Eq + Hash, V> NewIndex for HashMap<K, V> {
+    type Item = Option<V>;
     type Key = K;
 
-    fn new_index(&mut self, index: Self::Key, item: Self::Item) {
-        self.insert(index, item);
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+        match item {
+            Some(item) => self.insert(index, item),
+            None => self.remove(&index),
+        };
+        Ok(())
     }
 }
 
-// TODO: check for incoherent data with index. e.g.
-// Hashset [ "abc", "def", ]
-//
-// and in lua:
-// ```lua
-// set[1] = "xyz"
-// ```
-// this should modify the first value and not insert a new one.
-impl<T: Hash + Eq> NewIndex for HashSet<T> {
-    type Item = T;
-    type Key = usize;
+/// Assigning `nil` removes the entry.
+impl<K: Ord, V> NewIndex for BTreeMap<K, V> {
+    type Item = Option<V>;
+    type Key = K;
 
-    fn new_index(&mut self, _index: Self::Key, item: Self::Item) {
-        self.insert(item);
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+        match item {
+            Some(item) => self.insert(index, item),
+            None => self.remove(&index),
+        };
+        Ok(())
     }
 }
 
-impl<T: Ord> NewIndex for BTreeSet<T> {
-    type Item = T;
-    type Key = usize;
+/// Assigning `nil` removes the entry, `shift_remove` keeping the order of the others.
+#[cfg(feature = "indexmap")]
+impl<K: Eq + Hash, V, S: std::hash::BuildHasher> NewIndex for indexmap::IndexMap<K, V, S> {
+    type Item = Option<V>;
+    type Key = K;
 
-    fn new_index(&mut self, _index: Self::Key, item: Self::Item) {
-        self.insert(item);
+    fn new_index(&mut self, index: Self::Key, item: Self::Item) -> mlua::Result<()> {
+        match item {
+            Some(item) => self.insert(index, item),
+            None => self.shift_remove(&index),
+        };
+        Ok(())
     }
 }
 
@@ -0,0 +1,147 @@
+use mlua::{Lua, MultiValue, Table, Value};
+
+/// Registers `#[mlua_gen]` types into a table, like `Player::to_table`.
+pub type Register = fn(&Lua, &Table) -> mlua::Result<()>;
+
+/// Groups `#[mlua_gen]` types in a module that Lua loads with `require`.
+///
+/// ```ignore
+/// let mut module = Module::new("game");
+/// module
+///     .add(Player::to_table)
+///     .add(|lua, table| Enemy::to_table_as(lua, table, "Foe"));
+/// module.preload(&lua)?;
+/// ```
+///
+/// ```lua
+/// local game = require("game")
+/// local player = game.Player { name = "Ada" }
+/// ```
+#[derive(Clone)]
+pub struct Module {
+    name:      String,
+    registers: Vec<Register>,
+}
+
+impl Module {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name:      name.into(),
+            registers: vec![],
+        }
+    }
+
+    /// Adds the types registered by `register` to the module.
+    pub fn add(&mut self, register: Register) -> &mut Self {
+        self.registers.push(register);
+        self
+    }
+
+    /// Creates the table of the module, holding every added type.
+    pub fn to_table(&self, lua: &Lua) -> mlua::Result<Table> {
+        let table = lua.create_table()?;
+        for register in &self.registers {
+            register(lua, &table)?;
+        }
+        Ok(table)
+    }
+
+    /// Registers the module in `package.preload`, so that the first `require` of its name creates
+    /// its table. Luau has no `package.preload`: the module gets the first of `package.loaders`
+    /// instead.
+    pub fn preload(&self, lua: &Lua) -> mlua::Result<()> {
+        let module = self.clone();
+        let loader = lua.create_function(move |lua, _: MultiValue| module.to_table(lua))?;
+        let package: Table = lua.globals().get("package")?;
+
+        #[cfg(not(feature = "luau"))]
+        {
+            let preload: Table = package.get("preload")?;
+            preload.set(self.name.as_str(), loader)
+        }
+        #[cfg(feature = "luau")]
+        {
+            let name = self.name.clone();
+            let searcher = lua.create_function(move |_, required: String| {
+                Ok((required == name).then(|| loader.clone()))
+            })?;
+            let loaders: Table = package.get("loaders")?;
+            // `table.insert(loaders, 1, searcher)`
+            for position in (1..=loaders.raw_len()).rev() {
+                loaders.raw_set(position + 1, loaders.raw_get::<Value>(position)?)?;
+            }
+            loaders.raw_set(1, searcher)
+        }
+    }
+}
+
+/// Walks the dotted `name` from `root`, creating the missing intermediate tables: `a.b.C` gives
+/// the `a.b` table and `"C"`. Only the own fields of the tables are looked at, so that a sandbox
+/// environment falling back to the globals through `__index` gets its own tables.
+#[doc(hidden)]
+pub fn nested_table<'n>(lua: &Lua, root: &Table, name: &'n str) -> mlua::Result<(Table, &'n str)> {
+    let Some((path, key)) = name.rsplit_once('.') else {
+        return Ok((root.clone(), name));
+    };
+    let mut table = root.clone();
+    for segment in path.split('.') {
+        if segment.is_empty() {
+            return Err(mlua::Error::runtime(format!(
+                "`{name}` has an empty segment"
+            )));
+        }
+        table = match table.raw_get::<Value>(segment)? {
+            Value::Table(inner) => inner,
+            Value::Nil => {
+                let inner = lua.create_table()?;
+                table.raw_set(segment, &inner)?;
+                inner
+            },
+            other => {
+                return Err(mlua::Error::runtime(format!(
+                    "`{segment}` of `{name}` is a {}, not a table",
+                    other.type_name()
+                )));
+            },
+        };
+    }
+    Ok((table, key))
+}
+
+/// Generates the `luaopen_<name>` entry point of a native Lua module, built as a `cdylib`, whose
+/// table holds every listed type.
+///
+/// It goes through `#[mlua::lua_module]`, which needs the `module` feature. mlua refuses `send` in
+/// module mode, and `send` is a default feature:
+///
+/// ```toml
+/// [lib]
+/// crate-type = ["cdylib"]
+///
+/// [dependencies]
+/// mlua = { version = "0.10", features = ["lua54", "module"] }
+/// mlua-gen = { version = "0.2", default-features = false, features = ["lua54", "module"] }
+/// ```
+///
+/// ```ignore
+/// mlua_gen::lua_module!(game, [Player, Enemy]);
+/// ```
+///
+/// ```lua
+/// local game = require("game")
+/// local player = game.Player { name = "Ada" }
+/// ```
+#[cfg(feature = "module")]
+#[macro_export]
+macro_rules! lua_module {
+    ($name:ident, [$($t:ty),* $(,)?]) => {
+        #[::mlua::lua_module]
+        fn $name(lua: &::mlua::Lua) -> ::mlua::Result<::mlua::Table> {
+            let mut module = $crate::Module::new(stringify!($name));
+            $(module.add(
+                <$t as $crate::LuaBuilder<_, ::mlua::Lua, ::mlua::Error, ::mlua::Table>>::to_table,
+            );)*
+            module.to_table(lua)
+        }
+    };
+}
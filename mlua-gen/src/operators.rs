@@ -0,0 +1,181 @@
+//! Operator metamethods (`__add`, `__eq`, `__lt`, ...) for `#[mlua_gen]`
+//! types, registered when the matching Rust trait is implemented.
+
+use {
+    crate::proxy::{PathStep, Resolver},
+    mlua::{FromLua, Function, Lua, MetaMethod, Table, UserDataMethods, Value},
+    std::any::type_name,
+};
+
+/// Metatable entry of a proxy table resolving it to a copy of the value it
+/// points at, so proxies can be used as operands.
+const PROXY_VALUE: &str = "__mlua_gen_value";
+
+/// Arithmetic metamethod, both operands being converted with [`operand`], or the right one
+/// with `f64::from_lua` for the operators taking a number.
+#[doc(hidden)]
+pub type Arithmetic = fn(&Lua, Value, Value) -> mlua::Result<Value>;
+
+/// Comparison metamethod, both operands being converted with [`operand`].
+#[doc(hidden)]
+pub type Comparison = fn(&Lua, (Value, Value)) -> mlua::Result<bool>;
+
+/// Operator metamethods of a type, `None` when it doesn't implement the trait of the operator.
+/// Built by [`operators!`](crate::operators).
+#[doc(hidden)]
+#[derive(Clone, Copy)]
+pub struct Operators {
+    pub add:    Option<Arithmetic>,
+    pub sub:    Option<Arithmetic>,
+    pub mul:    Option<Arithmetic>,
+    pub div:    Option<Arithmetic>,
+    pub rem:    Option<Arithmetic>,
+    pub neg:    Option<fn(&Lua, Value) -> mlua::Result<Value>>,
+    pub eq:     Option<Comparison>,
+    /// `__lt` and `__le`.
+    pub ord:    Option<[Comparison; 2]>,
+    /// `__concat`, for `Display` types.
+    pub concat: bool,
+}
+
+impl Operators {
+    fn arithmetic(self) -> [(MetaMethod, Option<Arithmetic>); 5] {
+        [
+            (MetaMethod::Add, self.add),
+            (MetaMethod::Sub, self.sub),
+            (MetaMethod::Mul, self.mul),
+            (MetaMethod::Div, self.div),
+            (MetaMethod::Mod, self.rem),
+        ]
+    }
+
+    fn comparisons(self) -> [(MetaMethod, Option<Comparison>); 3] {
+        let [lt, le] = self.ord.map_or([None; 2], |ord| ord.map(Some));
+        [
+            (MetaMethod::Eq, self.eq),
+            (MetaMethod::Lt, lt),
+            (MetaMethod::Le, le),
+        ]
+    }
+}
+
+/// `operators!(Type)`: the [`Operators`] of `Type`, with the operator traits it implements.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! operators {
+    ($ty:ty) => {
+        $crate::Operators {
+            add:    $crate::probe!($ty => add_operator),
+            sub:    $crate::probe!($ty => sub_operator),
+            mul:    $crate::probe!($ty => mul_operator),
+            div:    $crate::probe!($ty => div_operator),
+            rem:    $crate::probe!($ty => rem_operator),
+            neg:    $crate::probe!($ty => neg_operator),
+            eq:     $crate::probe!($ty => eq_operator),
+            ord:    $crate::probe!($ty => ord_operators),
+            concat: $crate::probe!($ty => display).is_some(),
+        }
+    };
+}
+
+/// Operand of an operator metamethod. `T` userdata (or `Arc<Mutex<T>>`) and
+/// proxies are copied out, anything else goes through `FromLua`.
+#[doc(hidden)]
+pub fn operand<T: Clone + FromLua + 'static>(lua: &Lua, value: Value) -> mlua::Result<T> {
+    match value {
+        Value::UserData(ud) => crate::with_parent::<T, _>(&ud, |this| Ok(this.clone())),
+        Value::Table(table) => {
+            let resolve = match table.metatable() {
+                Some(mt) => mt.raw_get::<Option<Function>>(PROXY_VALUE)?,
+                None => None,
+            };
+            match resolve {
+                Some(resolve) => operand(lua, resolve.call(())?),
+                None => T::from_lua(Value::Table(table), lua),
+            }
+        },
+        value => T::from_lua(value, lua),
+    }
+}
+
+/// Comparison metamethod of the proxies of `T`, created once per Lua state: before Lua 5.3,
+/// two tables are only compared when they share the same metamethod.
+fn comparison<T: 'static>(
+    lua: &Lua,
+    metamethod: MetaMethod,
+    compare: Comparison,
+) -> mlua::Result<Function> {
+    let key = format!("mlua_gen.{}.{}", type_name::<T>(), metamethod.name());
+    if let Some(function) = lua.named_registry_value::<Option<Function>>(&key)? {
+        return Ok(function);
+    }
+    let function = lua.create_function(compare)?;
+    lua.set_named_registry_value(&key, &function)?;
+    Ok(function)
+}
+
+fn concat(_: &Lua, (lhs, rhs): (Value, Value)) -> mlua::Result<String> {
+    Ok(format!("{}{}", lhs.to_string()?, rhs.to_string()?))
+}
+
+/// Registers the `operators` of `T` on its `UserData`.
+#[doc(hidden)]
+pub fn add_operator_meta_methods<T, M: UserDataMethods<T>>(methods: &mut M, operators: Operators) {
+    for (metamethod, operator) in operators.arithmetic() {
+        if let Some(operator) = operator {
+            methods.add_meta_function(metamethod, move |lua, (lhs, rhs)| operator(lua, lhs, rhs));
+        }
+    }
+    if let Some(neg) = operators.neg {
+        methods.add_meta_function(MetaMethod::Unm, neg);
+    }
+    for (metamethod, compare) in operators.comparisons() {
+        if let Some(compare) = compare {
+            methods.add_meta_function(metamethod, compare);
+        }
+    }
+    if operators.concat {
+        methods.add_meta_function(MetaMethod::Concat, concat);
+    }
+}
+
+/// Same as [`add_operator_meta_methods`] but for the metatable of a proxy
+/// pointing at a `T`.
+#[doc(hidden)]
+pub fn set_proxy_operators<T: 'static>(
+    lua: &Lua,
+    mt: &Table,
+    ctx: Resolver,
+    path: Vec<PathStep>,
+    operators: Operators,
+) -> mlua::Result<()> {
+    mt.set(
+        PROXY_VALUE,
+        lua.create_function(move |lua, ()| (ctx.get)(lua, &path))?,
+    )?;
+
+    for (metamethod, operator) in operators.arithmetic() {
+        if let Some(operator) = operator {
+            mt.set(
+                metamethod.name(),
+                lua.create_function(move |lua, (lhs, rhs)| operator(lua, lhs, rhs))?,
+            )?;
+        }
+    }
+    if let Some(neg) = operators.neg {
+        mt.set(MetaMethod::Unm.name(), lua.create_function(neg)?)?;
+    }
+    for (metamethod, compare) in operators.comparisons() {
+        if let Some(compare) = compare {
+            mt.set(
+                metamethod.name(),
+                comparison::<T>(lua, metamethod, compare)?,
+            )?;
+        }
+    }
+    if operators.concat {
+        mt.set(MetaMethod::Concat.name(), lua.create_function(concat)?)?;
+    }
+
+    Ok(())
+}
@@ -2,33 +2,154 @@
 //! and walk the path against the root on each Lua `__index`/`__newindex`.
 
 use {
-    mlua::{AnyUserData, FromLua, Lua, Table, Value},
+    crate::{
+        collection::{CollectionMethod, CollectionMethods, collection_method, written_element},
+        trait_helpers::rust_position,
+    },
+    mlua::{
+        AnyUserData,
+        FromLua,
+        Function,
+        IntoLua,
+        Lua,
+        MaybeSend,
+        MultiValue,
+        ObjectLike,
+        Table,
+        Value,
+    },
     std::{
-        collections::{BTreeMap, HashMap},
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+        fmt,
         hash::Hash,
+        rc::Rc,
         sync::Arc,
     },
 };
 
-/// One hop in a path rooted at the parent `AnyUserData`.
-#[doc(hidden)]
+/// One hop in a path rooted at the parent `AnyUserData`, as handed to the [`OnChange`] and
+/// [`Validate`] hooks: `world.units[1].hp = 3` is
+/// `[Field("units"), Index(1), Field("hp")]`.
+///
+/// New kinds of hops may be added, so matches on it need a wildcard arm.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum PathStep {
+    /// Named field, by its Lua name: the one of `#[mlua(rename)]` when set.
     Field(&'static str),
-    /// 0-based tuple index.
+    /// Tuple field, by its 0-based Rust position whatever the `index_base`.
     Tuple(usize),
-    /// Raw Lua key; the generated arm converts it.
+    /// Element of a collection or of a user `Index`, by the Lua key as written: positions are
+    /// offset by the `index_base`, and the generated arm converts it.
     Index(Value),
+    /// Enum variant, by its Lua name: the lowercased variant, like `moving` for `Mode::Moving`.
     Variant(&'static str),
+    /// `Some` of an `Option` field, which has no Lua key: `unit.stats.hp` is
+    /// `[Field("stats"), Unwrap, Field("hp")]` for `stats: Option<Stats>`. It errors once the
+    /// option is `None`.
+    Unwrap,
+}
+
+/// What `__pairs` iterates and `__len` counts on the value at a path.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub enum ProxyKeys {
+    /// `base..base + len`, for sequences.
+    Sequence { base: usize, len: usize },
+    /// Readable fields of a struct or variant.
+    Fields(&'static [&'static str]),
+    /// Keys of a map, or the readable positions of a tuple.
+    Keys(Vec<Value>),
+}
+
+impl ProxyKeys {
+    pub const fn len(&self) -> usize {
+        match self {
+            Self::Sequence { len, .. } => *len,
+            Self::Fields(fields) => fields.len(),
+            Self::Keys(keys) => keys.len(),
+        }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `key` is one of the keys.
+    pub fn contains(&self, key: &Value) -> bool {
+        match self {
+            Self::Sequence { base, len } => {
+                key.as_usize()
+                    .is_some_and(|position| (*base..*base + *len).contains(&position))
+            },
+            Self::Fields(fields) => {
+                key.as_string()
+                    .is_some_and(|key| fields.iter().any(|field| key == *field))
+            },
+            Self::Keys(keys) => keys.contains(key),
+        }
+    }
+
+    pub(crate) fn key(&self, lua: &Lua, position: usize) -> mlua::Result<Option<Value>> {
+        Ok(match self {
+            Self::Sequence { base, len } if position < *len => {
+                Some((position + base).into_lua(lua)?)
+            },
+            Self::Fields(fields) => {
+                match fields.get(position) {
+                    Some(field) => Some(field.into_lua(lua)?),
+                    None => None,
+                }
+            },
+            Self::Keys(keys) => keys.get(position).cloned(),
+            Self::Sequence { .. } => None,
+        })
+    }
 }
 
-/// Type-erased walkers + `on_set` hook for one root. Cheap to clone.
+/// `on_set` hook bound to its root.
+#[doc(hidden)]
+pub type OnSet = Arc<dyn Fn() + Send + Sync>;
+
+/// `on_change` hook of a root `T`: the root after the write, the path that
+/// was written, the previous value and the new one.
+///
+/// Only the hooks of the root run, see [`Validate`].
+pub type OnChange<T> = fn(&T, &[PathStep], &Value, &Value);
+
+/// [`OnChange`] bound to its root.
+type ErasedOnChange =
+    Arc<dyn Fn(&Lua, &[PathStep], &Value, &Value) -> mlua::Result<()> + Send + Sync>;
+
+/// `validate` hook of a root `T`: the root before the write, the path about
+/// to be written and the proposed value. Returning an error aborts the write.
+///
+/// Only the hooks of the root run: `player.stats.percent = 5` goes through the
+/// `validate` of `Player`, with the path `[Field("stats"), Field("percent")]`,
+/// not through the one of `Stats`, which only runs on `Stats` userdata. A root
+/// checks the invariants of the types nested in it.
+pub type Validate<T> = fn(&T, &[PathStep], &Value) -> mlua::Result<()>;
+
+/// [`Validate`] bound to its root.
+type ErasedValidate = Arc<dyn Fn(&Lua, &[PathStep], &Value) -> mlua::Result<()> + Send + Sync>;
+
+/// Type-erased walkers + `validate`/`on_set`/`on_change` hooks for one root.
+/// Cheap to clone.
 #[doc(hidden)]
 #[derive(Clone)]
 pub struct Resolver {
-    pub get:    Arc<dyn Fn(&Lua, &[PathStep]) -> mlua::Result<Value> + Send + Sync>,
-    pub set:    Arc<dyn Fn(&Lua, &[PathStep], Value) -> mlua::Result<()> + Send + Sync>,
-    pub on_set: Option<Arc<dyn Fn() + Send + Sync>>,
+    pub get:       Arc<dyn Fn(&Lua, &[PathStep]) -> mlua::Result<Value> + Send + Sync>,
+    pub set:       Arc<dyn Fn(&Lua, &[PathStep], Value) -> mlua::Result<()> + Send + Sync>,
+    pub tostring:  Arc<dyn Fn(&Lua, &[PathStep]) -> mlua::Result<String> + Send + Sync>,
+    pub keys:      Arc<dyn Fn(&Lua, &[PathStep]) -> mlua::Result<ProxyKeys> + Send + Sync>,
+    pub call: Arc<
+        dyn Fn(&Lua, &[PathStep], CollectionMethod, MultiValue) -> mlua::Result<Value>
+            + Send
+            + Sync,
+    >,
+    pub on_set:    Option<OnSet>,
+    pub on_change: Option<ErasedOnChange>,
+    pub validate:  Option<ErasedValidate>,
 }
 
 impl Resolver {
@@ -37,111 +158,352 @@ impl Resolver {
             hook();
         }
     }
+
+    /// Validates and writes `value` at `steps`, then fires the hooks.
+    pub fn set_and_fire(&self, lua: &Lua, steps: &[PathStep], value: Value) -> mlua::Result<()> {
+        self.write_and_fire(lua, steps, value, |value| (self.set)(lua, steps, value))
+    }
+
+    /// Calls `method` on the collection at `steps`, then fires the hooks with
+    /// the element it writes.
+    pub fn call_and_fire(
+        &self,
+        lua: &Lua,
+        steps: &[PathStep],
+        method: CollectionMethod,
+        args: MultiValue,
+    ) -> mlua::Result<Value> {
+        match written_element(lua, self, steps, method, &args)? {
+            Some((path, value)) => {
+                self.write_and_fire(lua, &path, value, |_| (self.call)(lua, steps, method, args))
+            },
+            None => (self.call)(lua, steps, method, args),
+        }
+    }
+
+    /// Runs `write`, which writes `value` at `steps`, between the `validate`
+    /// hook and the `on_change`/`on_set` ones. The previous value is only
+    /// read when there is an `on_change` hook, which gets the stored value
+    /// for plain values, e.g. after a `clamp`, and the written one for tables
+    /// and userdata.
+    fn write_and_fire<R>(
+        &self,
+        lua: &Lua,
+        steps: &[PathStep],
+        value: Value,
+        write: impl FnOnce(Value) -> mlua::Result<R>,
+    ) -> mlua::Result<R> {
+        if let Some(validate) = &self.validate {
+            validate(lua, steps, &value)?;
+        }
+        let result = match &self.on_change {
+            Some(hook) => {
+                let old = (self.get)(lua, steps).unwrap_or(Value::Nil);
+                let result = write(value.clone())?;
+                let new = match value {
+                    Value::Table(_) | Value::UserData(_) => value,
+                    value => (self.get)(lua, steps).unwrap_or(value),
+                };
+                hook(lua, steps, &old, &new)?;
+                result
+            },
+            None => write(value)?,
+        };
+        self.fire_on_set();
+        Ok(result)
+    }
 }
 
 /// Build a resolver for `root: T`. Sub-proxies clone the result; `T` is
 /// erased after this point.
 #[doc(hidden)]
 pub fn make_resolver<T: MluaGenProject + 'static>(
+    lua: &Lua,
     root: AnyUserData,
-    on_set: Option<Arc<dyn Fn() + Send + Sync>>,
-) -> Resolver {
-    // `AnyUserData::clone` duplicates the registry reference, not `T`: both
-    // closures below borrow the same underlying userdata. Sub-proxies share
-    // these closures via `Arc`, so at most two registry-handle clones exist
-    // per top-level proxy entry, regardless of nesting depth.
-    let root_get = root.clone();
+    on_set: Option<OnSet>,
+    on_change: Option<OnChange<T>>,
+    validate: Option<Validate<T>>,
+) -> mlua::Result<Resolver> {
+    // The closures hold the root through the registry, which is `Send + Sync` whatever mlua's
+    // `send` feature, and look the userdata up on each call. Sub-proxies share them via `Arc`, so a
+    // top-level proxy entry costs a single registry slot, regardless of nesting depth.
+    let root = Arc::new(lua.create_registry_value(root)?);
+    let root_get = Arc::clone(&root);
+    let root_tostring = Arc::clone(&root);
+    let root_keys = Arc::clone(&root);
+    let root_call = Arc::clone(&root);
+    let root_change = Arc::clone(&root);
+    let root_validate = Arc::clone(&root);
     let root_set = root;
-    Resolver {
+    Ok(Resolver {
         get: Arc::new(move |lua, steps| {
-            crate::with_parent::<T, _>(&root_get, |this| this.project_get(lua, steps))
+            crate::with_parent::<T, _>(&lua.registry_value(&root_get)?, |this| {
+                this.project_get(lua, steps)
+            })
         }),
         set: Arc::new(move |lua, steps, value| {
-            crate::with_parent_mut::<T, _>(&root_set, |this| this.project_set(lua, steps, value))
+            crate::with_parent_mut::<T, _>(&lua.registry_value(&root_set)?, |this| {
+                this.project_set(lua, steps, value)
+            })
+        }),
+        tostring: Arc::new(move |lua, steps| {
+            crate::with_parent::<T, _>(&lua.registry_value(&root_tostring)?, |this| {
+                this.project_tostring(lua, steps)
+            })
+        }),
+        keys: Arc::new(move |lua, steps| {
+            crate::with_parent::<T, _>(&lua.registry_value(&root_keys)?, |this| {
+                this.project_keys(lua, steps)
+            })
+        }),
+        call: Arc::new(move |lua, steps, method, args| {
+            crate::with_parent_mut::<T, _>(&lua.registry_value(&root_call)?, |this| {
+                this.project_call(lua, steps, method, args)
+            })
         }),
         on_set,
-    }
+        on_change: on_change.map(|hook| {
+            let erased: ErasedOnChange = Arc::new(move |lua, steps, old, new| {
+                crate::with_parent::<T, _>(&lua.registry_value(&root_change)?, |this| {
+                    hook(this, steps, old, new);
+                    Ok(())
+                })
+            });
+            erased
+        }),
+        validate: validate.map(|hook| {
+            let erased: ErasedValidate = Arc::new(move |lua, steps, value| {
+                crate::with_parent::<T, _>(&lua.registry_value(&root_validate)?, |this| {
+                    hook(this, steps, value)
+                })
+            });
+            erased
+        }),
+    })
 }
 
 /// Generated per `#[mlua_gen]` type. `project_*` walk one step;
 /// `build_proxy` returns a Lua table whose meta-methods route through
 /// `Resolver`.
-#[doc(hidden)]
+///
+/// Bound the type parameters of the fields reached from Lua with it, like
+/// `struct Outer<T: MluaGenProject> { inner: T }`, for Lua to write through
+/// them: `outer.inner.x = 5`. Without it, the fields mentioning a type
+/// parameter are plain values, converted with `IntoLua`/`FromLua`: Lua reads
+/// copies, so only writing a whole value reaches Rust, unless the `nightly`
+/// feature specializes on it.
 pub trait MluaGenProject {
+    /// Lua position of the first tuple field and of the first element of the `Vec`/`Index`
+    /// fields, set with `#[mlua_gen(index_base = 0)]`. The tables building a whole tuple struct or
+    /// variant, and the snapshots of variants, are keyed from it too.
+    const INDEX_BASE: usize = 1;
+
     fn project_get(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<Value>;
     fn project_set(&mut self, lua: &Lua, steps: &[PathStep], value: Value) -> mlua::Result<()>;
+    /// `__tostring` of the value at `steps`, `self` when empty.
+    fn project_tostring(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<String>;
+    /// Keys of the value at `steps`, `self` when empty.
+    fn project_keys(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<ProxyKeys>;
+    /// Calls `method` on the collection at `steps`.
+    fn project_call(
+        &mut self,
+        lua: &Lua,
+        steps: &[PathStep],
+        method: CollectionMethod,
+        args: MultiValue,
+    ) -> mlua::Result<Value>;
     fn build_proxy(
         lua: &Lua,
         ctx: Resolver,
         path: Vec<PathStep>,
         vis: Visibility,
     ) -> mlua::Result<Table>;
-}
 
-/// Specialization probe: forwards to `MluaGenProject` when available, else
-/// errors. Lets unreachable codegen arms type-check on non-`mlua_gen` types.
-#[doc(hidden)]
-pub trait MluaGenProjectMaybe {
-    fn maybe_project_get(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<Value>;
-    fn maybe_project_set(
-        &mut self,
-        lua: &Lua,
-        steps: &[PathStep],
-        value: Value,
-    ) -> mlua::Result<()>;
-    fn maybe_build_proxy(
+    /// What Lua sees at `path`: the table of `build_proxy`, or `nil` for a `None` option.
+    fn proxy_value(
         lua: &Lua,
         ctx: Resolver,
         path: Vec<PathStep>,
         vis: Visibility,
-    ) -> mlua::Result<Table>;
+    ) -> mlua::Result<Value> {
+        Self::build_proxy(lua, ctx, path, vis).map(Value::Table)
+    }
+
+    /// Checks the `#[mlua(...)]` constraints of the fields of `self` and of the `mlua_gen` values
+    /// under them, clamping the `clamp` ones. For the values `serde` builds, which don't go
+    /// through the `FromLua` of their fields.
+    fn check_fields(&mut self, _lua: &Lua) -> mlua::Result<()> {
+        Ok(())
+    }
+
+    /// Resolver of the `Self` held by `root`, firing the `on_set`, `on_change` and `validate`
+    /// hooks of `Self`.
+    fn root_resolver(lua: &Lua, root: AnyUserData) -> mlua::Result<Resolver>
+    where
+        Self: Sized + 'static,
+    {
+        make_resolver::<Self>(lua, root, None, None, None)
+    }
 }
 
-impl<T> MluaGenProjectMaybe for T {
-    default fn maybe_project_get(&self, _lua: &Lua, _steps: &[PathStep]) -> mlua::Result<Value> {
-        Err(mlua::Error::runtime("type is not #[mlua_gen]"))
+/// Proxy of the fields of a `T` behind a shared root, locking `root` on each access.
+///
+/// It is for the roots that mlua can't make a userdata of `T` itself, like an
+/// `Arc<tokio::sync::Mutex<T>>` or an `Arc<tokio::sync::RwLock<T>>`. The methods of `T` aren't
+/// reachable through it.
+pub fn root_proxy<T, R>(lua: &Lua, root: R) -> mlua::Result<Table>
+where
+    T: MluaGenProject + 'static,
+    R: MaybeSend + 'static,
+{
+    let root = lua.create_any_userdata(root)?;
+    T::build_proxy(
+        lua,
+        T::root_resolver(lua, root)?,
+        Vec::new(),
+        Visibility::Both,
+    )
+}
+
+/// How generated code walks a field of type `T`, picked by `probe!(T, Idx => field_ops)`: as an
+/// `mlua_gen` type, a collection of them, a collection of leaf values, or a leaf value. `base` is
+/// the index base of the type holding the field, `name` the field, for the errors.
+#[doc(hidden)]
+pub struct FieldOps<T> {
+    /// What Lua reads from the field: a proxy, or `None` for a copy through `IntoLua`.
+    pub proxy:    Option<fn(&Lua, Resolver, Vec<PathStep>, Visibility) -> mlua::Result<Value>>,
+    /// Reads under the field, `steps` being non-empty.
+    pub get:      fn(&T, &Lua, usize, &'static str, &[PathStep]) -> mlua::Result<Value>,
+    /// Writes under the field, `steps` being non-empty.
+    pub set:      fn(&mut T, &Lua, usize, &'static str, &[PathStep], Value) -> mlua::Result<()>,
+    /// `__tostring` of a value under the field, `steps` being non-empty.
+    pub tostring: fn(&T, &Lua, usize, &'static str, &[PathStep]) -> mlua::Result<String>,
+    /// Keys of the field, or of a value under it.
+    pub keys:     fn(&T, &Lua, usize, &'static str, &[PathStep]) -> mlua::Result<ProxyKeys>,
+    /// Checks the constraints of the `mlua_gen` values in the field, see
+    /// [`MluaGenProject::check_fields`].
+    pub check:    fn(&mut T, &Lua, usize) -> mlua::Result<()>,
+    /// Calls `method` on the field, or on a collection under it.
+    pub call: fn(
+        &mut T,
+        &Lua,
+        usize,
+        &'static str,
+        &[PathStep],
+        CollectionMethod,
+        MultiValue,
+    ) -> mlua::Result<Value>,
+}
+
+/// `Some` is proxied like `T` through [`PathStep::Unwrap`], `None` reads as `nil`. Its own keys
+/// are those of a sequence of zero or one value.
+impl<T: MluaGenProject + FromLua> MluaGenProject for Option<T> {
+    const INDEX_BASE: usize = T::INDEX_BASE;
+
+    fn project_get(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<Value> {
+        match steps.split_first() {
+            None => {
+                match self {
+                    Some(value) => value.project_get(lua, &[]),
+                    None => Ok(Value::Nil),
+                }
+            },
+            Some((PathStep::Unwrap, rest)) => {
+                match self {
+                    Some(value) => value.project_get(lua, rest),
+                    None if rest.is_empty() => Ok(Value::Nil),
+                    None => Err(became_nil()),
+                }
+            },
+            Some(_) => Err(bad_step("Option")),
+        }
     }
 
-    default fn maybe_project_set(
-        &mut self,
-        _lua: &Lua,
-        _steps: &[PathStep],
-        _value: Value,
-    ) -> mlua::Result<()> {
-        Err(mlua::Error::runtime("type is not #[mlua_gen]"))
+    fn project_set(&mut self, lua: &Lua, steps: &[PathStep], value: Value) -> mlua::Result<()> {
+        match steps.split_first() {
+            None => {
+                *self = Self::from_lua(value, lua)?;
+                Ok(())
+            },
+            Some((PathStep::Unwrap, rest)) => {
+                self.as_mut()
+                    .ok_or_else(became_nil)?
+                    .project_set(lua, rest, value)
+            },
+            Some(_) => Err(bad_step("Option")),
+        }
     }
 
-    default fn maybe_build_proxy(
-        _lua: &Lua,
-        _ctx: Resolver,
-        _path: Vec<PathStep>,
-        _vis: Visibility,
-    ) -> mlua::Result<Table> {
-        Err(mlua::Error::runtime("type is not #[mlua_gen]"))
+    fn project_tostring(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<String> {
+        match (steps.split_first(), self) {
+            (None, None) => Ok("nil".to_owned()),
+            (None, Some(value)) => value.project_tostring(lua, &[]),
+            (Some((PathStep::Unwrap, rest)), Some(value)) => value.project_tostring(lua, rest),
+            (Some((PathStep::Unwrap, _)), None) => Err(became_nil()),
+            (Some(_), _) => Err(bad_step("Option")),
+        }
     }
-}
 
-impl<T: MluaGenProject> MluaGenProjectMaybe for T {
-    fn maybe_project_get(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<Value> {
-        <T as MluaGenProject>::project_get(self, lua, steps)
+    fn project_keys(&self, lua: &Lua, steps: &[PathStep]) -> mlua::Result<ProxyKeys> {
+        match steps.split_first() {
+            None => {
+                Ok(ProxyKeys::Sequence {
+                    base: T::INDEX_BASE,
+                    len:  usize::from(self.is_some()),
+                })
+            },
+            Some((PathStep::Unwrap, rest)) => {
+                self.as_ref()
+                    .ok_or_else(became_nil)?
+                    .project_keys(lua, rest)
+            },
+            Some(_) => Err(bad_step("Option")),
+        }
     }
 
-    fn maybe_project_set(
+    fn project_call(
         &mut self,
         lua: &Lua,
         steps: &[PathStep],
-        value: Value,
-    ) -> mlua::Result<()> {
-        <T as MluaGenProject>::project_set(self, lua, steps, value)
+        method: CollectionMethod,
+        args: MultiValue,
+    ) -> mlua::Result<Value> {
+        match steps.split_first() {
+            Some((PathStep::Unwrap, rest)) => {
+                self.as_mut()
+                    .ok_or_else(became_nil)?
+                    .project_call(lua, rest, method, args)
+            },
+            _ => Err(bad_step("Option")),
+        }
     }
 
-    fn maybe_build_proxy(
+    fn build_proxy(
         lua: &Lua,
         ctx: Resolver,
-        path: Vec<PathStep>,
+        mut path: Vec<PathStep>,
         vis: Visibility,
     ) -> mlua::Result<Table> {
-        <T as MluaGenProject>::build_proxy(lua, ctx, path, vis)
+        path.push(PathStep::Unwrap);
+        T::build_proxy(lua, ctx, path, vis)
+    }
+
+    fn proxy_value(
+        lua: &Lua,
+        ctx: Resolver,
+        path: Vec<PathStep>,
+        vis: Visibility,
+    ) -> mlua::Result<Value> {
+        if (ctx.keys)(lua, &path)?.is_empty() {
+            Ok(Value::Nil)
+        } else {
+            Self::build_proxy(lua, ctx, path, vis).map(Value::Table)
+        }
+    }
+
+    fn check_fields(&mut self, lua: &Lua) -> mlua::Result<()> {
+        self.as_mut()
+            .map_or(Ok(()), |value| value.check_fields(lua))
     }
 }
 
@@ -150,6 +512,184 @@ pub fn bad_step(context: &str) -> mlua::Error {
     mlua::Error::runtime(format!("invalid path step: {context}"))
 }
 
+/// Error of a proxy whose `Option` became `None` after it was created.
+fn became_nil() -> mlua::Error {
+    mlua::Error::runtime("value became nil under proxy")
+}
+
+/// Value rejected by a `#[mlua(range/max_len/validate)]` field constraint. `path` leads from
+/// `owner` to the field, in Lua syntax: `.settings.fps`, `.list[1].fps`. The owner is unknown
+/// while the error goes up through a collection, until the field holding it is reached.
+#[derive(Debug)]
+struct InvalidField {
+    owner:  Option<&'static str>,
+    path:   String,
+    reason: String,
+}
+
+impl fmt::Display for InvalidField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let owner = self.owner.unwrap_or_default();
+        write!(
+            f,
+            "invalid value for `{owner}{}`: {}",
+            self.path, self.reason
+        )
+    }
+}
+
+impl std::error::Error for InvalidField {}
+
+/// `err` with `step` prepended to its path when it is an [`InvalidField`].
+fn prefix_invalid(err: mlua::Error, owner: Option<&'static str>, step: &str) -> mlua::Error {
+    let invalid = match &err {
+        mlua::Error::ExternalError(inner) => inner.downcast_ref::<InvalidField>(),
+        _ => None,
+    };
+    match invalid {
+        Some(invalid) => {
+            mlua::Error::external(InvalidField {
+                owner,
+                path: format!("{step}{}", invalid.path),
+                reason: invalid.reason.clone(),
+            })
+        },
+        None => err,
+    }
+}
+
+/// Error of a write rejected by a field constraint, `step` being the field in Lua syntax:
+/// `.hp`, or `[1]` for a tuple field.
+#[doc(hidden)]
+pub fn invalid_field<R: fmt::Display>(owner: &'static str, step: &str, reason: R) -> mlua::Error {
+    mlua::Error::external(InvalidField {
+        owner:  Some(owner),
+        path:   step.to_owned(),
+        reason: reason.to_string(),
+    })
+}
+
+/// `err`, raised under the field `step` of `owner`, with its path going through the field.
+#[doc(hidden)]
+pub fn within_field(err: mlua::Error, owner: &'static str, step: &str) -> mlua::Error {
+    prefix_invalid(err, Some(owner), step)
+}
+
+/// `err`, raised under the element of a collection at the Lua `key`, with its path going through
+/// the element.
+pub(crate) fn within_elem<K: fmt::Display>(err: mlua::Error, key: K) -> mlua::Error {
+    prefix_invalid(err, None, &format!("[{key}]"))
+}
+
+/// Lua key as written in a path: `1`, `"name"`.
+pub(crate) struct LuaKey<'a>(pub(crate) &'a Value);
+
+impl fmt::Display for LuaKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Integer(integer) => write!(f, "{integer}"),
+            Value::Number(number) => write!(f, "{number}"),
+            Value::Boolean(boolean) => write!(f, "{boolean}"),
+            Value::String(string) => write!(f, "{:?}", string.to_string_lossy()),
+            other => f.write_str(other.type_name()),
+        }
+    }
+}
+
+/// Registers `__tostring` on a proxy metatable, stringifying the value the
+/// proxy points at through the resolver.
+#[doc(hidden)]
+pub fn set_proxy_tostring(
+    lua: &Lua,
+    mt: &Table,
+    ctx: Resolver,
+    path: Vec<PathStep>,
+) -> mlua::Result<()> {
+    mt.set(
+        "__tostring",
+        lua.create_function(move |lua, _: Value| (ctx.tostring)(lua, &path))?,
+    )
+}
+
+/// Stateful `__pairs` iterator over `keys`, reading each value through `O`'s
+/// own `__index` so nested values stay proxies.
+#[doc(hidden)]
+pub fn pairs_iterator<O: ObjectLike + FromLua + 'static>(
+    lua: &Lua,
+    keys: ProxyKeys,
+) -> mlua::Result<Function> {
+    let mut position = 0;
+    lua.create_function_mut(move |lua, (this, _): (O, Value)| {
+        let Some(key) = keys.key(lua, position)? else {
+            return Ok((Value::Nil, Value::Nil));
+        };
+        position += 1;
+        let value: Value = this.get(key.clone())?;
+        Ok((key, value))
+    })
+}
+
+/// Metamethods of `pairs`: `__pairs`, and `__iter` of the generalized iteration of Luau.
+///
+/// Proxies are plain tables, so what Lua sees of them depends on the backend:
+/// - Lua 5.1 and LuaJIT ignore `__len` and `__pairs` on tables, and `__pairs` on userdata: `#`
+///   and `pairs` see an empty table, `keys()` lists the keys of collections;
+/// - Luau's `pairs` and `ipairs` ignore `__iter`, only `for k, v in proxy do` calls it;
+/// - Lua 5.1, LuaJIT and Luau raise an error when comparing a table with a userdata, even when
+///   both resolve to the same type, so a proxy only compares with another proxy there.
+#[doc(hidden)]
+#[cfg(not(feature = "luau"))]
+pub const PAIRS_METAMETHODS: &[&str] = &["__pairs"];
+#[doc(hidden)]
+#[cfg(feature = "luau")]
+pub const PAIRS_METAMETHODS: &[&str] = &["__pairs", "__iter"];
+
+/// Registers `__pairs` on a proxy metatable, iterating the keys of the value
+/// the proxy points at.
+#[doc(hidden)]
+pub fn set_proxy_pairs(
+    lua: &Lua,
+    mt: &Table,
+    ctx: Resolver,
+    path: Vec<PathStep>,
+) -> mlua::Result<()> {
+    let pairs = lua.create_function(move |lua, proxy: Table| {
+        let keys = (ctx.keys)(lua, &path)?;
+        Ok((pairs_iterator::<Table>(lua, keys)?, proxy, Value::Nil))
+    })?;
+    for metamethod in PAIRS_METAMETHODS {
+        mt.set(*metamethod, &pairs)?;
+    }
+    // `ipairs` of Lua 5.2 reads raw fields unless there is an `__ipairs`
+    #[cfg(feature = "lua52")]
+    mt.set(
+        "__ipairs",
+        lua.create_function(|lua, proxy: Table| {
+            let next = lua.create_function(|_, (proxy, position): (Table, usize)| {
+                let value: Value = proxy.get(position + 1)?;
+                let position = (!value.is_nil()).then_some(position + 1);
+                Ok((position, value))
+            })?;
+            Ok((next, proxy, 0))
+        })?,
+    )?;
+    Ok(())
+}
+
+/// Registers `__len` on a collection proxy metatable.
+#[doc(hidden)]
+pub fn set_proxy_len(
+    lua: &Lua,
+    mt: &Table,
+    ctx: Resolver,
+    path: Vec<PathStep>,
+) -> mlua::Result<()> {
+    mt.set(
+        "__len",
+        lua.create_function(move |lua, _: Value| Ok((ctx.keys)(lua, &path)?.len()))?,
+    )
+}
+
 /// Top-level proxy gating. Sub-proxies always use `Both`; only the entry
 /// table respects the parent field's `set` visibility.
 #[doc(hidden)]
@@ -161,22 +701,31 @@ pub enum Visibility {
 }
 
 /// Proxy table for a collection of leaf values: `__index(i)` reads,
-/// `__newindex(i, v)` writes via the resolver and fires `on_set`.
+/// `__newindex(i, v)` writes via the resolver and fires `on_set`. `__len`
+/// and `__pairs` walk the collection, and `__index` also resolves the
+/// collection methods (`push`, `remove`, ...) when it has `methods`.
 #[doc(hidden)]
 pub fn build_indexed_proxy_leaf(
     lua: &Lua,
     ctx: Resolver,
     path: Vec<PathStep>,
     vis: Visibility,
+    methods: bool,
 ) -> mlua::Result<Table> {
     let table = lua.create_table()?;
     let mt = lua.create_table()?;
+    set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+    set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
+    set_proxy_len(lua, &mt, ctx.clone(), path.clone())?;
 
     let ctx_g = ctx.clone();
     let path_g = path.clone();
     mt.set(
         "__index",
         lua.create_function(move |lua, (_, key): (Value, Value)| {
+            if methods && let Some(method) = collection_method(lua, &ctx_g, &path_g, &key, vis)? {
+                return Ok(Value::Function(method));
+            }
             let mut p = path_g.clone();
             p.push(PathStep::Index(key));
             (ctx_g.get)(lua, &p)
@@ -191,9 +740,7 @@ pub fn build_indexed_proxy_leaf(
             lua.create_function(move |lua, (_, key, value): (Value, Value, Value)| {
                 let mut p = path_s.clone();
                 p.push(PathStep::Index(key));
-                (ctx_s.set)(lua, &p, value)?;
-                ctx_s.fire_on_set();
-                Ok(())
+                ctx_s.set_and_fire(lua, &p, value)
             })?,
         )?;
     }
@@ -203,7 +750,8 @@ pub fn build_indexed_proxy_leaf(
 }
 
 /// Proxy table for a collection of `mlua_gen` elements: `__index(i)`
-/// returns a sub-proxy; `__newindex(i, v)` replaces the whole element.
+/// returns a sub-proxy, or `nil` for a missing key so that `ipairs` stops past
+/// the end of a sequence; `__newindex(i, v)` replaces the whole element.
 #[doc(hidden)]
 pub fn build_indexed_proxy_struct<Elem: MluaGenProject + 'static>(
     lua: &Lua,
@@ -213,20 +761,24 @@ pub fn build_indexed_proxy_struct<Elem: MluaGenProject + 'static>(
 ) -> mlua::Result<Table> {
     let table = lua.create_table()?;
     let mt = lua.create_table()?;
+    set_proxy_tostring(lua, &mt, ctx.clone(), path.clone())?;
+    set_proxy_pairs(lua, &mt, ctx.clone(), path.clone())?;
+    set_proxy_len(lua, &mt, ctx.clone(), path.clone())?;
 
     let ctx_g = ctx.clone();
     let path_g = path.clone();
     mt.set(
         "__index",
         lua.create_function(move |lua, (_, key): (Value, Value)| {
+            if let Some(method) = collection_method(lua, &ctx_g, &path_g, &key, vis)? {
+                return Ok(Value::Function(method));
+            }
+            if !(ctx_g.keys)(lua, &path_g)?.contains(&key) {
+                return Ok(Value::Nil);
+            }
             let mut p = path_g.clone();
             p.push(PathStep::Index(key));
-            Ok(Value::Table(<Elem as MluaGenProject>::build_proxy(
-                lua,
-                ctx_g.clone(),
-                p,
-                Visibility::Both,
-            )?))
+            <Elem as MluaGenProject>::proxy_value(lua, ctx_g.clone(), p, Visibility::Both)
         })?,
     )?;
 
@@ -238,9 +790,7 @@ pub fn build_indexed_proxy_struct<Elem: MluaGenProject + 'static>(
             lua.create_function(move |lua, (_, key, value): (Value, Value, Value)| {
                 let mut p = path_s.clone();
                 p.push(PathStep::Index(key));
-                (ctx_s.set)(lua, &p, value)?;
-                ctx_s.fire_on_set();
-                Ok(())
+                ctx_s.set_and_fire(lua, &p, value)
             })?,
         )?;
     }
@@ -249,177 +799,496 @@ pub fn build_indexed_proxy_struct<Elem: MluaGenProject + 'static>(
     Ok(table)
 }
 
-/// Indexable collection of `MluaGenProject` elements. Specialized for
-/// `Vec`, `HashMap`, `BTreeMap`; default impl errors.
+/// Indexable collection of `MluaGenProject` elements: the sequences and the maps.
 #[doc(hidden)]
-pub trait CollectionProject {
-    const IS_COLLECTION_OF_MLUA_GEN: bool = false;
-
-    fn project_get_elem(&self, lua: &Lua, key: Value, steps: &[PathStep]) -> mlua::Result<Value>;
+pub trait CollectionProject: CollectionMethods {
+    fn project_get_elem(
+        &self,
+        lua: &Lua,
+        base: usize,
+        key: Value,
+        steps: &[PathStep],
+    ) -> mlua::Result<Value>;
 
     fn project_set_elem(
         &mut self,
         lua: &Lua,
+        base: usize,
         key: Value,
         steps: &[PathStep],
         value: Value,
     ) -> mlua::Result<()>;
 
+    fn project_tostring_elem(
+        &self,
+        lua: &Lua,
+        base: usize,
+        key: Value,
+        steps: &[PathStep],
+    ) -> mlua::Result<String>;
+
+    fn collection_keys(&self, lua: &Lua, base: usize) -> mlua::Result<ProxyKeys>;
+
+    fn project_call_elem(
+        &mut self,
+        lua: &Lua,
+        base: usize,
+        key: Value,
+        steps: &[PathStep],
+        method: CollectionMethod,
+        args: MultiValue,
+    ) -> mlua::Result<Value>;
+
+    fn project_keys_elem(
+        &self,
+        lua: &Lua,
+        base: usize,
+        key: Value,
+        steps: &[PathStep],
+    ) -> mlua::Result<ProxyKeys>;
+
     fn build_collection_proxy(
         lua: &Lua,
         ctx: Resolver,
         path: Vec<PathStep>,
         vis: Visibility,
     ) -> mlua::Result<Table>;
+
+    /// [`MluaGenProject::check_fields`] of every element.
+    fn check_elems(&mut self, lua: &Lua, base: usize) -> mlua::Result<()>;
+
+    /// `FromLua` converting element by element, for the errors of the constraints of an element
+    /// to name it by its key in the Lua table.
+    fn elems_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self>
+    where
+        Self: Sized;
 }
 
-impl<C> CollectionProject for C {
-    default const IS_COLLECTION_OF_MLUA_GEN: bool = false;
+/// `CollectionProject` of a sequence indexed by position. Writing right past the end appends with
+/// `$push`, like `t[#t + 1] = v`, when the sequence can grow.
+macro_rules! sequence_project {
+    ([$($generics:tt)*] $sequence:ty, $elem:ty $(, $push:ident)?) => {
+        impl<$($generics)*> CollectionProject for $sequence
+        where
+            $elem: MluaGenProject + FromLua + IntoLua + 'static,
+        {
+            fn project_get_elem(
+                &self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<Value> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                let elem = self
+                    .get(idx)
+                    .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
+                elem.project_get(lua, steps)
+            }
 
-    default fn project_get_elem(
-        &self,
-        _lua: &Lua,
-        _key: Value,
-        _steps: &[PathStep],
-    ) -> mlua::Result<Value> {
-        Err(mlua::Error::runtime("not a collection of mlua_gen"))
-    }
+            fn project_set_elem(
+                &mut self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                steps: &[PathStep],
+                value: Value,
+            ) -> mlua::Result<()> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                $(
+                    if idx == self.len() && steps.is_empty() {
+                        self.$push(<$elem>::from_lua(value, lua)?);
+                        return Ok(());
+                    }
+                )?
+                let elem = self
+                    .get_mut(idx)
+                    .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
+                elem.project_set(lua, steps, value)
+            }
 
-    default fn project_set_elem(
-        &mut self,
-        _lua: &Lua,
-        _key: Value,
-        _steps: &[PathStep],
-        _value: Value,
-    ) -> mlua::Result<()> {
-        Err(mlua::Error::runtime("not a collection of mlua_gen"))
-    }
+            fn project_tostring_elem(
+                &self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<String> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                let elem = self
+                    .get(idx)
+                    .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
+                elem.project_tostring(lua, steps)
+            }
 
-    default fn build_collection_proxy(
-        _lua: &Lua,
-        _ctx: Resolver,
-        _path: Vec<PathStep>,
-        _vis: Visibility,
-    ) -> mlua::Result<Table> {
-        Err(mlua::Error::runtime("not a collection of mlua_gen"))
-    }
+            fn collection_keys(&self, _lua: &Lua, base: usize) -> mlua::Result<ProxyKeys> {
+                Ok(ProxyKeys::Sequence {
+                    base,
+                    len: self.len(),
+                })
+            }
+
+            fn project_call_elem(
+                &mut self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                steps: &[PathStep],
+                method: CollectionMethod,
+                args: MultiValue,
+            ) -> mlua::Result<Value> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                let elem = self
+                    .get_mut(idx)
+                    .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
+                elem.project_call(lua, steps, method, args)
+            }
+
+            fn project_keys_elem(
+                &self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<ProxyKeys> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                let elem = self
+                    .get(idx)
+                    .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
+                elem.project_keys(lua, steps)
+            }
+
+            fn build_collection_proxy(
+                lua: &Lua,
+                ctx: Resolver,
+                path: Vec<PathStep>,
+                vis: Visibility,
+            ) -> mlua::Result<Table> {
+                build_indexed_proxy_struct::<$elem>(lua, ctx, path, vis)
+            }
+
+            fn check_elems(&mut self, lua: &Lua, base: usize) -> mlua::Result<()> {
+                for (idx, elem) in self.iter_mut().enumerate() {
+                    elem.check_fields(lua).map_err(|err| within_elem(err, base + idx))?;
+                }
+                Ok(())
+            }
+
+            fn elems_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self> {
+                let elems = Table::from_lua(value, lua)?
+                    .sequence_values::<Value>()
+                    .enumerate()
+                    .map(|(idx, elem)| {
+                        <$elem>::from_lua(elem?, lua).map_err(|err| within_elem(err, idx + 1))
+                    })
+                    .collect::<mlua::Result<Vec<_>>>()?;
+                let len = elems.len();
+                Self::try_from(elems).map_err(|_| mlua::Error::FromLuaConversionError {
+                    from:    "table",
+                    to:      std::any::type_name::<Self>().to_owned(),
+                    message: Some(format!("unexpected length {len}")),
+                })
+            }
+        }
+    };
 }
 
-impl<T: MluaGenProject + 'static> CollectionProject for Vec<T> {
-    const IS_COLLECTION_OF_MLUA_GEN: bool = true;
+sequence_project!([T] Vec<T>, T, push);
+sequence_project!([T] VecDeque<T>, T, push_back);
+sequence_project!([T, const N: usize] [T; N], T);
+sequence_project!([T] Box<[T]>, T);
+#[cfg(feature = "smallvec")]
+sequence_project!([A: smallvec::Array] smallvec::SmallVec<A>, A::Item, push);
 
-    fn project_get_elem(&self, lua: &Lua, key: Value, steps: &[PathStep]) -> mlua::Result<Value> {
-        let one_based: usize = FromLua::from_lua(key, lua)?;
-        let idx = one_based
-            .checked_sub(1)
-            .ok_or_else(|| mlua::Error::runtime("Lua indices start at 1"))?;
-        let elem = self
-            .get(idx)
-            .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
-        elem.project_get(lua, steps)
-    }
+/// `CollectionProject` of a map of `V` indexed by `K`. Assigning a whole entry inserts it, or
+/// removes it with `$remove` when the value is nil.
+macro_rules! map_project {
+    ([$($generics:tt)*] $map:ty, $remove:ident, $($key:tt)+) => {
+        impl<$($generics)*> CollectionProject for $map
+        where
+            K: $($key)+ + Clone + FromLua + IntoLua + 'static,
+            V: MluaGenProject + FromLua + IntoLua + 'static,
+        {
+            fn project_get_elem(
+                &self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<Value> {
+                let key = K::from_lua(key, lua)?;
+                let elem = self
+                    .get(&key)
+                    .ok_or_else(|| mlua::Error::runtime("key not found"))?;
+                elem.project_get(lua, steps)
+            }
 
-    fn project_set_elem(
-        &mut self,
-        lua: &Lua,
-        key: Value,
-        steps: &[PathStep],
-        value: Value,
-    ) -> mlua::Result<()> {
-        let one_based: usize = FromLua::from_lua(key, lua)?;
-        let idx = one_based
-            .checked_sub(1)
-            .ok_or_else(|| mlua::Error::runtime("Lua indices start at 1"))?;
-        let elem = self
-            .get_mut(idx)
-            .ok_or_else(|| mlua::Error::runtime("index out of bounds"))?;
-        elem.project_set(lua, steps, value)
-    }
+            fn project_set_elem(
+                &mut self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                steps: &[PathStep],
+                value: Value,
+            ) -> mlua::Result<()> {
+                let key = K::from_lua(key, lua)?;
+                // A whole entry is inserted, or removed by assigning nil
+                if steps.is_empty() {
+                    match value {
+                        Value::Nil => self.$remove(&key),
+                        value => self.insert(key, V::from_lua(value, lua)?),
+                    };
+                    return Ok(());
+                }
+                let elem = self
+                    .get_mut(&key)
+                    .ok_or_else(|| mlua::Error::runtime("key not found"))?;
+                elem.project_set(lua, steps, value)
+            }
 
-    fn build_collection_proxy(
-        lua: &Lua,
-        ctx: Resolver,
-        path: Vec<PathStep>,
-        vis: Visibility,
-    ) -> mlua::Result<Table> {
-        build_indexed_proxy_struct::<T>(lua, ctx, path, vis)
-    }
+            fn project_tostring_elem(
+                &self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<String> {
+                let key = K::from_lua(key, lua)?;
+                let elem = self
+                    .get(&key)
+                    .ok_or_else(|| mlua::Error::runtime("key not found"))?;
+                elem.project_tostring(lua, steps)
+            }
+
+            fn collection_keys(&self, lua: &Lua, _base: usize) -> mlua::Result<ProxyKeys> {
+                self.keys()
+                    .map(|key| key.clone().into_lua(lua))
+                    .collect::<mlua::Result<_>>()
+                    .map(ProxyKeys::Keys)
+            }
+
+            fn project_call_elem(
+                &mut self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                steps: &[PathStep],
+                method: CollectionMethod,
+                args: MultiValue,
+            ) -> mlua::Result<Value> {
+                let key = K::from_lua(key, lua)?;
+                let elem = self
+                    .get_mut(&key)
+                    .ok_or_else(|| mlua::Error::runtime("key not found"))?;
+                elem.project_call(lua, steps, method, args)
+            }
+
+            fn project_keys_elem(
+                &self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                steps: &[PathStep],
+            ) -> mlua::Result<ProxyKeys> {
+                let key = K::from_lua(key, lua)?;
+                let elem = self
+                    .get(&key)
+                    .ok_or_else(|| mlua::Error::runtime("key not found"))?;
+                elem.project_keys(lua, steps)
+            }
+
+            fn build_collection_proxy(
+                lua: &Lua,
+                ctx: Resolver,
+                path: Vec<PathStep>,
+                vis: Visibility,
+            ) -> mlua::Result<Table> {
+                build_indexed_proxy_struct::<V>(lua, ctx, path, vis)
+            }
+
+            fn check_elems(&mut self, lua: &Lua, _base: usize) -> mlua::Result<()> {
+                for (key, elem) in self.iter_mut() {
+                    if let Err(err) = elem.check_fields(lua) {
+                        return Err(within_elem(err, LuaKey(&key.clone().into_lua(lua)?)));
+                    }
+                }
+                Ok(())
+            }
+
+            fn elems_from_lua(value: Value, lua: &Lua) -> mlua::Result<Self> {
+                Table::from_lua(value, lua)?
+                    .pairs::<Value, Value>()
+                    .map(|pair| {
+                        let (key, elem) = pair?;
+                        let elem =
+                            V::from_lua(elem, lua).map_err(|err| within_elem(err, LuaKey(&key)))?;
+                        Ok((K::from_lua(key, lua)?, elem))
+                    })
+                    .collect()
+            }
+        }
+    };
 }
 
-impl<K, V> CollectionProject for HashMap<K, V>
-where
-    K: Eq + Hash + FromLua + 'static,
-    V: MluaGenProject + 'static,
-{
-    const IS_COLLECTION_OF_MLUA_GEN: bool = true;
-
-    fn project_get_elem(&self, lua: &Lua, key: Value, steps: &[PathStep]) -> mlua::Result<Value> {
-        let key = K::from_lua(key, lua)?;
-        let elem = self
-            .get(&key)
-            .ok_or_else(|| mlua::Error::runtime("key not found"))?;
-        elem.project_get(lua, steps)
-    }
+map_project!([K, V] HashMap<K, V>, remove, Eq + Hash);
+map_project!([K, V] BTreeMap<K, V>, remove, Ord);
+// `shift_remove` keeps the order of the other entries
+#[cfg(feature = "indexmap")]
+map_project!(
+    [K, V, S: std::hash::BuildHasher + Default] indexmap::IndexMap<K, V, S>,
+    shift_remove,
+    Eq + Hash
+);
 
-    fn project_set_elem(
-        &mut self,
-        lua: &Lua,
-        key: Value,
-        steps: &[PathStep],
-        value: Value,
-    ) -> mlua::Result<()> {
-        let key = K::from_lua(key, lua)?;
-        let elem = self
-            .get_mut(&key)
-            .ok_or_else(|| mlua::Error::runtime("key not found"))?;
-        elem.project_set(lua, steps, value)
-    }
+/// Collection of leaf values proxied element by element: the sequences, indexed by position, and
+/// the sets, seen from Lua as tables of `value = true`.
+#[doc(hidden)]
+pub trait LeafCollection: CollectionMethods {
+    /// Whether the proxy resolves the collection methods. Sets have none, so that `set[v]` is a
+    /// membership test for every `v`, including `"remove"`.
+    const METHODS: bool = true;
 
-    fn build_collection_proxy(
-        lua: &Lua,
-        ctx: Resolver,
-        path: Vec<PathStep>,
-        vis: Visibility,
-    ) -> mlua::Result<Table> {
-        build_indexed_proxy_struct::<V>(lua, ctx, path, vis)
-    }
+    /// `nil` past the end of a sequence, or for a value that isn't in a set.
+    fn leaf_get(&self, lua: &Lua, base: usize, key: Value) -> mlua::Result<Value>;
+    fn leaf_set(&mut self, lua: &Lua, base: usize, key: Value, value: Value) -> mlua::Result<()>;
+    /// `base..base + len` for sequences, the elements for sets.
+    fn leaf_keys(&self, lua: &Lua, base: usize) -> mlua::Result<ProxyKeys>;
 }
 
-impl<K, V> CollectionProject for BTreeMap<K, V>
-where
-    K: Ord + FromLua + 'static,
-    V: MluaGenProject + 'static,
-{
-    const IS_COLLECTION_OF_MLUA_GEN: bool = true;
-
-    fn project_get_elem(&self, lua: &Lua, key: Value, steps: &[PathStep]) -> mlua::Result<Value> {
-        let key = K::from_lua(key, lua)?;
-        let elem = self
-            .get(&key)
-            .ok_or_else(|| mlua::Error::runtime("key not found"))?;
-        elem.project_get(lua, steps)
-    }
+/// `LeafCollection` of a sequence, whose writes go through `NewIndex`: growable ones append one
+/// past the end.
+macro_rules! leaf_sequence {
+    ([$($generics:tt)*] $sequence:ty, $elem:ty) => {
+        impl<$($generics)*> LeafCollection for $sequence
+        where
+            $sequence: crate::NewIndex<Key = usize, Item = $elem>,
+            $elem: Clone + FromLua + IntoLua,
+        {
+            fn leaf_get(&self, lua: &Lua, base: usize, key: Value) -> mlua::Result<Value> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                self.get(idx).cloned().into_lua(lua)
+            }
 
-    fn project_set_elem(
-        &mut self,
-        lua: &Lua,
-        key: Value,
-        steps: &[PathStep],
-        value: Value,
-    ) -> mlua::Result<()> {
-        let key = K::from_lua(key, lua)?;
-        let elem = self
-            .get_mut(&key)
-            .ok_or_else(|| mlua::Error::runtime("key not found"))?;
-        elem.project_set(lua, steps, value)
-    }
+            fn leaf_set(
+                &mut self,
+                lua: &Lua,
+                base: usize,
+                key: Value,
+                value: Value,
+            ) -> mlua::Result<()> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                crate::NewIndex::new_index(self, idx, <$elem>::from_lua(value, lua)?)
+            }
 
-    fn build_collection_proxy(
-        lua: &Lua,
-        ctx: Resolver,
-        path: Vec<PathStep>,
-        vis: Visibility,
-    ) -> mlua::Result<Table> {
-        build_indexed_proxy_struct::<V>(lua, ctx, path, vis)
+            fn leaf_keys(&self, _lua: &Lua, base: usize) -> mlua::Result<ProxyKeys> {
+                Ok(ProxyKeys::Sequence {
+                    base,
+                    len: self.len(),
+                })
+            }
+        }
+    };
+}
+
+leaf_sequence!([T] Vec<T>, T);
+leaf_sequence!([T] VecDeque<T>, T);
+leaf_sequence!([T, const N: usize] [T; N], T);
+leaf_sequence!([T] Box<[T]>, T);
+#[cfg(feature = "smallvec")]
+leaf_sequence!([A: smallvec::Array] smallvec::SmallVec<A>, A::Item);
+
+/// `LeafCollection` of a shared slice, which is read-only.
+macro_rules! leaf_shared_slice {
+    ($shared:ident) => {
+        impl<T: Clone + IntoLua> LeafCollection for $shared<[T]> {
+            fn leaf_get(&self, lua: &Lua, base: usize, key: Value) -> mlua::Result<Value> {
+                let idx = rust_position(usize::from_lua(key, lua)?, base)?;
+                self.get(idx).cloned().into_lua(lua)
+            }
+
+            fn leaf_set(
+                &mut self,
+                _lua: &Lua,
+                _base: usize,
+                _key: Value,
+                _value: Value,
+            ) -> mlua::Result<()> {
+                Err(mlua::Error::runtime(format!(
+                    "`{}` is read-only",
+                    std::any::type_name::<Self>()
+                )))
+            }
+
+            fn leaf_keys(&self, _lua: &Lua, base: usize) -> mlua::Result<ProxyKeys> {
+                Ok(ProxyKeys::Sequence {
+                    base,
+                    len: self.len(),
+                })
+            }
+        }
+    };
+}
+
+leaf_shared_slice!(Rc);
+leaf_shared_slice!(Arc);
+
+/// Whether `present`, the value assigned to `set[v]`, keeps `v` in the set.
+fn set_membership(present: &Value) -> mlua::Result<bool> {
+    match present {
+        Value::Nil | Value::Boolean(false) => Ok(false),
+        Value::Boolean(true) => Ok(true),
+        other => {
+            Err(mlua::Error::runtime(format!(
+                "set elements can only be assigned `true` or `nil`, got a {}",
+                other.type_name()
+            )))
+        },
     }
 }
+
+/// `LeafCollection` of a set: `set[v]` tests membership, `set[v] = true` inserts and
+/// `set[v] = nil` removes.
+macro_rules! leaf_set {
+    ($set:ident, $($bound:tt)+) => {
+        impl<T> LeafCollection for $set<T>
+        where
+            T: $($bound)+ + Clone + FromLua + IntoLua,
+        {
+            const METHODS: bool = false;
+
+            fn leaf_get(&self, lua: &Lua, _base: usize, key: Value) -> mlua::Result<Value> {
+                Ok(if self.contains(&T::from_lua(key, lua)?) {
+                    Value::Boolean(true)
+                } else {
+                    Value::Nil
+                })
+            }
+
+            fn leaf_set(
+                &mut self,
+                lua: &Lua,
+                _base: usize,
+                key: Value,
+                value: Value,
+            ) -> mlua::Result<()> {
+                let key = T::from_lua(key, lua)?;
+                if set_membership(&value)? {
+                    self.insert(key);
+                } else {
+                    self.remove(&key);
+                }
+                Ok(())
+            }
+
+            fn leaf_keys(&self, lua: &Lua, _base: usize) -> mlua::Result<ProxyKeys> {
+                self.iter()
+                    .map(|value| value.clone().into_lua(lua))
+                    .collect::<mlua::Result<_>>()
+                    .map(ProxyKeys::Keys)
+            }
+        }
+    };
+}
+
+leaf_set!(HashSet, Hash + Eq);
+leaf_set!(BTreeSet, Ord);
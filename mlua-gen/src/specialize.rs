@@ -0,0 +1,113 @@
+//! Dispatch on the traits a type implements once its type parameters are known, behind the
+//! `nightly` feature.
+//!
+//! In a generic type, [`probe!`](crate::probe) only sees the bounds of the where clauses: without
+//! a `T: MluaGenProject` bound, a `Vec<T>` field is a collection of leaf values, even when `T` is
+//! an `mlua_gen` type. [`Specialize`] picks the level again after monomorphization, from the most
+//! specific to the one `probe!` found, so a concrete type gets the same level either way.
+
+use {
+    crate::{
+        LeafCollection,
+        MluaGenProject,
+        proxy::{CollectionProject, FieldOps},
+        trait_helpers::{
+            Probe,
+            probes::{FieldOpsCollection, FieldOpsLeafCollection, FieldOpsProject},
+        },
+    },
+    mlua::{Lua, Value},
+};
+
+/// Replaces `found`, what [`probe!`](crate::probe) picked from the bounds, by the level of the
+/// concrete type when it is more specific.
+#[doc(hidden)]
+pub trait Specialize<V> {
+    fn specialize(self, found: V) -> V;
+}
+
+impl<T, Idx, V> Specialize<V> for Probe<T, Idx> {
+    default fn specialize(self, found: V) -> V {
+        found
+    }
+}
+
+impl<T, Idx> Specialize<FieldOps<T>> for Probe<T, Idx> {
+    fn specialize(self, found: FieldOps<T>) -> FieldOps<T> {
+        <T as SpecializeProject<Idx>>::field_ops()
+            .or_else(<T as SpecializeCollection<Idx>>::field_ops)
+            .or_else(<T as SpecializeLeafCollection<Idx>>::field_ops)
+            .unwrap_or(found)
+    }
+}
+
+impl<T> Specialize<fn(Value, &Lua) -> mlua::Result<T>> for Probe<T> {
+    fn specialize(
+        self,
+        found: fn(Value, &Lua) -> mlua::Result<T>,
+    ) -> fn(Value, &Lua) -> mlua::Result<T> {
+        <T as SpecializeCollection>::from_lua().unwrap_or(found)
+    }
+}
+
+/// [`FieldOpsProject`] when `T` is an `mlua_gen` type.
+trait SpecializeProject<Idx>: Sized {
+    fn field_ops() -> Option<FieldOps<Self>>;
+}
+
+impl<T, Idx> SpecializeProject<Idx> for T {
+    default fn field_ops() -> Option<FieldOps<T>> {
+        None
+    }
+}
+
+impl<T: MluaGenProject, Idx> SpecializeProject<Idx> for T {
+    fn field_ops() -> Option<FieldOps<T>> {
+        Some(FieldOpsProject::field_ops(&&&&&&&Probe::<T, Idx>::new()))
+    }
+}
+
+/// [`FieldOpsCollection`] when `T` is a collection of `mlua_gen` elements, and the conversion of
+/// [`CollectionProject::elems_from_lua`].
+trait SpecializeCollection<Idx = usize>: Sized {
+    fn field_ops() -> Option<FieldOps<Self>>;
+
+    fn from_lua() -> Option<fn(Value, &Lua) -> mlua::Result<Self>>;
+}
+
+impl<T, Idx> SpecializeCollection<Idx> for T {
+    default fn field_ops() -> Option<FieldOps<T>> {
+        None
+    }
+
+    default fn from_lua() -> Option<fn(Value, &Lua) -> mlua::Result<T>> {
+        None
+    }
+}
+
+impl<T: CollectionProject, Idx> SpecializeCollection<Idx> for T {
+    fn field_ops() -> Option<FieldOps<T>> {
+        Some(FieldOpsCollection::field_ops(&&&&&&Probe::<T, Idx>::new()))
+    }
+
+    fn from_lua() -> Option<fn(Value, &Lua) -> mlua::Result<T>> {
+        Some(T::elems_from_lua)
+    }
+}
+
+/// [`FieldOpsLeafCollection`] when `T` is a collection of leaf values indexed by position.
+trait SpecializeLeafCollection<Idx>: Sized {
+    fn field_ops() -> Option<FieldOps<Self>>;
+}
+
+impl<T, Idx> SpecializeLeafCollection<Idx> for T {
+    default fn field_ops() -> Option<FieldOps<T>> {
+        None
+    }
+}
+
+impl<T: LeafCollection> SpecializeLeafCollection<usize> for T {
+    fn field_ops() -> Option<FieldOps<T>> {
+        Some(FieldOpsLeafCollection::field_ops(&&&&&Probe::<T>::new()))
+    }
+}
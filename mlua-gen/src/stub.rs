@@ -0,0 +1,68 @@
+//! LuaLS / EmmyLua annotations for `#[mlua_gen]` types, so editors running
+//! lua-language-server can complete what the Rust side exposes.
+
+use std::{fmt, io, path::Path};
+
+/// Automatically `impl`ed when using `#[mlua_gen]`.
+///
+/// Field types are mapped on a best-effort basis: numbers, strings, booleans, `Option`,
+/// `Vec`/maps/sets are translated, any other type is assumed to be an other `#[mlua_gen]` class.
+/// Return types of `impl = [...]` entries aren't known by the macro and are written as `any`.
+pub trait LuaStub {
+    /// Name used by [`crate::LuaBuilder::to_globals`].
+    const LUA_NAME: &'static str;
+
+    /// `---@class` definition of the userdata: readable/writable fields, methods and, for
+    /// enums, one optional field per variant.
+    fn lua_class_stub() -> String;
+
+    /// Definition of the global table registered by [`crate::LuaBuilder::to_globals_as`] under
+    /// `name`: constructors, variants and functions.
+    fn lua_global_stub(name: &str) -> String;
+}
+
+/// Collects the stubs of several types, and writes them as one `.d.lua` file.
+///
+/// ```ignore
+/// LuaStubs::new()
+///     .add::<Player>()
+///     .add_as::<Optional<i32>>("OptionalInt")
+///     .write("types.d.lua")?;
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct LuaStubs {
+    chunks: Vec<String>,
+}
+
+impl LuaStubs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `T`, registered under its Rust name.
+    pub fn add<T: LuaStub>(&mut self) -> &mut Self {
+        self.add_as::<T>(T::LUA_NAME)
+    }
+
+    /// Adds `T`, registered under `name` (Cf. [`crate::LuaBuilder::to_globals_as`]).
+    pub fn add_as<T: LuaStub>(&mut self, name: &str) -> &mut Self {
+        self.chunks.push(T::lua_class_stub());
+        self.chunks.push(T::lua_global_stub(name));
+        self
+    }
+
+    /// Writes every collected stub to `path`.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for LuaStubs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "---@meta")?;
+        for chunk in &self.chunks {
+            write!(f, "\n{chunk}")?;
+        }
+        Ok(())
+    }
+}
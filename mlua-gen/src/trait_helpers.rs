@@ -1,163 +1,749 @@
+//! Dispatch on the traits a type implements, on stable Rust.
+//!
+//! [`probe!`] calls a method on `&&&&&&&&Probe<T, Idx>`. Each family of methods has one impl per
+//! level, on `Probe` behind a different number of `&`, so method resolution picks the level with
+//! the most `&` whose bounds hold: the levels go from the most specific to the fallback. In generic
+//! code, the bounds that hold are the ones of the where clauses.
+
 use {
-    crate::NewIndex,
-    mlua::{FromLua, IntoLua},
-    std::ops::{Index, IndexMut},
+    crate::{
+        LeafCollection,
+        MluaGenProject,
+        NewIndex,
+        collection::unsupported_method,
+        proxy::{
+            CollectionProject,
+            FieldOps,
+            LuaKey,
+            PathStep,
+            ProxyKeys,
+            bad_step,
+            build_indexed_proxy_leaf,
+            within_elem,
+        },
+    },
+    mlua::{FromLuaMulti, Lua, MultiValue, Value},
+    std::{
+        any::Any,
+        marker::PhantomData,
+        ops::Index,
+        panic::{self, AssertUnwindSafe},
+    },
 };
 
-/// Here to conditionnaly check if a type `impl Index<usize>`
+/// Type-level handle on a field of type `T`, indexed by `Idx` when it is an `Index<Idx>`.
 #[doc(hidden)]
-pub trait IsIndexable {
-    const IS_INDEXABLE: bool = false;
-    type IndexType: IntoLua;
+pub struct Probe<T, Idx = usize>(PhantomData<fn(T, Idx)>);
 
-    /// This method MUST only be called by `mlua-gen` generated code. Calling it from somewhere
-    /// else might produce unexpected panics.
-    #[doc(hidden)]
-    fn index_or_unreachable(&self, _index: usize) -> Self::IndexType {
-        unreachable!()
+impl<T, Idx> Probe<T, Idx> {
+    #[expect(clippy::new_without_default, reason = "Only built by `probe!`")]
+    pub const fn new() -> Self {
+        Self(PhantomData)
     }
 }
 
-impl<T> IsIndexable for T {
-    // dummy type
-    default type IndexType = u8;
+/// `probe!(Type => method)` or `probe!(Type, Idx => method)`: calls `method` on the most specific
+/// level of [`Probe<Type, Idx>`](Probe) that `Type` implements.
+#[cfg(not(feature = "nightly"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! probe {
+    ($ty:ty $(, $index:ty)? => $method:ident) => {{
+        use $crate::probes::*;
+        (&&&&&&&&$crate::Probe::<$ty $(, $index)?>::new()).$method()
+    }};
+}
+
+/// Same, the level being picked again once the type parameters are known, see
+/// [`Specialize`](crate::Specialize).
+#[cfg(feature = "nightly")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! probe {
+    ($ty:ty $(, $index:ty)? => $method:ident) => {{
+        use $crate::probes::*;
+        $crate::Specialize::specialize(
+            $crate::Probe::<$ty $(, $index)?>::new(),
+            (&&&&&&&&$crate::Probe::<$ty $(, $index)?>::new()).$method(),
+        )
+    }};
+}
+
+/// Converts the Lua key of an `Index<Idx>` to `Idx`: as a single value when it converts, else
+/// from a sequence, like `grid[{x, y}]` for `Index<(u32, u32)>`.
+#[doc(hidden)]
+pub trait FromLuaIndex: Sized {
+    fn from_lua_index(key: Value, lua: &Lua) -> mlua::Result<Self>;
+}
+
+impl<T: FromLuaMulti> FromLuaIndex for T {
+    fn from_lua_index(key: Value, lua: &Lua) -> mlua::Result<Self> {
+        let Value::Table(table) = key else {
+            return T::from_lua_multi(MultiValue::from_vec(vec![key]), lua);
+        };
+        T::from_lua_multi(MultiValue::from_vec(vec![Value::Table(table.clone())]), lua).or_else(
+            |_| T::from_lua_multi(table.sequence_values().collect::<mlua::Result<_>>()?, lua),
+        )
+    }
+}
 
-    default const IS_INDEXABLE: bool = false;
+/// 0-based index of the Lua `position`, which starts at `base`.
+pub(crate) fn rust_position(position: usize, base: usize) -> mlua::Result<usize> {
+    position
+        .checked_sub(base)
+        .ok_or_else(|| mlua::Error::runtime(format!("Lua indices start at {base}")))
+}
 
-    default fn index_or_unreachable(&self, index: usize) -> Self::IndexType {
-        unreachable!()
+/// `usize` indices are Lua positions, offset by `base`; the others are used as is.
+fn into_rust_index<Idx: 'static>(mut index: Idx, base: usize) -> mlua::Result<Idx> {
+    let any: &mut dyn Any = &mut index;
+    if let Some(position) = any.downcast_mut::<usize>() {
+        *position = rust_position(*position, base)?;
     }
+    Ok(index)
 }
 
-/// TODO: handle more indexable data types in std
-impl<T: Index<usize>> IsIndexable for T
+/// Types copied in and out as a whole even though they `impl Index<usize>`.
+#[doc(hidden)]
+pub trait PlainField {}
+
+// `IndexMap` is a map like the others: it isn't proxied by the position of its `Index<usize>`
+#[cfg(feature = "indexmap")]
+impl<K, V, S> PlainField for indexmap::IndexMap<K, V, S> {}
+
+/// `Display` when implemented, else `Debug`, else the type name: `display` is
+/// `probe!(T => display)`. Used for `__tostring`.
+#[doc(hidden)]
+pub fn lua_tostring<T>(value: &T, display: Option<fn(&T) -> String>) -> String {
+    display.map_or_else(
+        || std::any::type_name::<T>().to_owned(),
+        |display| display(value),
+    )
+}
+
+/// Runs `access`, a call to the user `Index`/`IndexMut` of the field `name`, raising its panic as
+/// a Lua error: indices aren't bound checked, an unknown one usually panics. Panics still abort
+/// with `panic = "abort"`.
+fn user_index<R>(name: &str, access: impl FnOnce() -> R) -> mlua::Result<R> {
+    panic::catch_unwind(AssertUnwindSafe(access)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("no message");
+        mlua::Error::runtime(format!("index of `{name}` panicked: {message}"))
+    })
+}
+
+/// Read of a user `Index<Idx>` field at `[key]`, see [`user_index`].
+fn index_get<T, Idx>(
+    this: &T,
+    lua: &Lua,
+    base: usize,
+    name: &'static str,
+    steps: &[PathStep],
+) -> mlua::Result<Value>
 where
-    <T as Index<usize>>::Output: Sized + 'static + Clone + mlua::IntoLua,
+    T: Index<Idx, Output: Clone + mlua::IntoLua>,
+    Idx: FromLuaIndex + 'static,
 {
-    type IndexType = <T as Index<usize>>::Output;
+    let [PathStep::Index(key)] = steps else {
+        return Err(bad_step(name));
+    };
+    let index = into_rust_index(Idx::from_lua_index(key.clone(), lua)?, base)?;
+    mlua::IntoLua::into_lua(user_index(name, || this[index].clone())?, lua)
+}
 
-    const IS_INDEXABLE: bool = true;
+/// Keys of the fields without any.
+fn no_keys<T>(
+    _this: &T,
+    _lua: &Lua,
+    _base: usize,
+    name: &'static str,
+    _steps: &[PathStep],
+) -> mlua::Result<ProxyKeys> {
+    Err(bad_step(name))
+}
 
-    fn index_or_unreachable(&self, index: usize) -> Self::IndexType {
-        self[index].clone()
+/// `__tostring` under the fields that only have leaf values under them.
+fn leaf_tostring<T>(
+    _this: &T,
+    _lua: &Lua,
+    _base: usize,
+    name: &'static str,
+    _steps: &[PathStep],
+) -> mlua::Result<String> {
+    Err(bad_step(name))
+}
+
+/// Constraint checks of the fields holding no `mlua_gen` value, which have none.
+const fn no_check<T>(_this: &mut T, _lua: &Lua, _base: usize) -> mlua::Result<()> {
+    Ok(())
+}
+
+/// Collection methods of the user `Index` fields, which have none.
+fn no_methods<T>(
+    _this: &mut T,
+    _lua: &Lua,
+    _base: usize,
+    name: &'static str,
+    steps: &[PathStep],
+    method: crate::CollectionMethod,
+    _args: MultiValue,
+) -> mlua::Result<Value> {
+    if steps.is_empty() {
+        Err(unsupported_method::<T>(method))
+    } else {
+        Err(bad_step(name))
     }
 }
 
+/// Levels of [`probe!`], from the most specific to the fallback of each family.
+#[doc(hidden)]
+pub mod probes {
+    use {
+        super::*,
+        crate::operand,
+        mlua::{FromLua, IntoLua},
+        std::{
+            fmt::{Debug, Display},
+            ops::{Add, Div, IndexMut, Mul, Neg, Rem, Sub},
+        },
+    };
+
+    /// Field of an `mlua_gen` type, proxied through its [`MluaGenProject`].
+    pub trait FieldOpsProject<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-// Same but with [`IndexMut`]
-pub trait IsMutIndexable {
-    type IndexType: FromLua;
+    impl<T: MluaGenProject, Idx> FieldOpsProject<T> for &&&&&&Probe<T, Idx> {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(T::proxy_value),
+                get:      |this, lua, _, _, steps| this.project_get(lua, steps),
+                set:      |this, lua, _, _, steps, value| this.project_set(lua, steps, value),
+                tostring: |this, lua, _, _, steps| this.project_tostring(lua, steps),
+                keys:     |this, lua, _, _, steps| this.project_keys(lua, steps),
+                check:    |this, lua, _| this.check_fields(lua),
+                call:     |this, lua, _, _, steps, method, args| {
+                    this.project_call(lua, steps, method, args)
+                },
+            }
+        }
+    }
 
-    const IS_MUT_INDEXABLE: bool = false;
+    /// Collection of `mlua_gen` elements, see [`CollectionProject`].
+    pub trait FieldOpsCollection<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-    #[doc(hidden)]
-    fn set_index_or_unreachable(&mut self, _index: usize, _val: Self::IndexType) {
-        unreachable!()
+    impl<T: CollectionProject, Idx> FieldOpsCollection<T> for &&&&&Probe<T, Idx> {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(|lua, ctx, path, vis| {
+                    T::build_collection_proxy(lua, ctx, path, vis).map(Value::Table)
+                }),
+                get:      |this, lua, base, name, steps| {
+                    match steps.split_first() {
+                        Some((PathStep::Index(key), rest)) => {
+                            this.project_get_elem(lua, base, key.clone(), rest)
+                        },
+                        _ => Err(bad_step(name)),
+                    }
+                },
+                set:      |this, lua, base, name, steps, value| {
+                    match steps.split_first() {
+                        Some((PathStep::Index(key), rest)) => {
+                            this.project_set_elem(lua, base, key.clone(), rest, value)
+                                .map_err(|err| within_elem(err, LuaKey(key)))
+                        },
+                        _ => Err(bad_step(name)),
+                    }
+                },
+                tostring: |this, lua, base, name, steps| {
+                    match steps.split_first() {
+                        Some((PathStep::Index(key), rest)) => {
+                            this.project_tostring_elem(lua, base, key.clone(), rest)
+                        },
+                        _ => Err(bad_step(name)),
+                    }
+                },
+                keys:     |this, lua, base, name, steps| {
+                    match steps.split_first() {
+                        None => this.collection_keys(lua, base),
+                        Some((PathStep::Index(key), rest)) => {
+                            this.project_keys_elem(lua, base, key.clone(), rest)
+                        },
+                        Some(_) => Err(bad_step(name)),
+                    }
+                },
+                check:    |this, lua, base| this.check_elems(lua, base),
+                call:     |this, lua, base, name, steps, method, args| {
+                    match steps.split_first() {
+                        None => this.call_method(lua, base, method, args),
+                        Some((PathStep::Index(key), rest)) => {
+                            this.project_call_elem(lua, base, key.clone(), rest, method, args)
+                                .map_err(|err| within_elem(err, LuaKey(key)))
+                        },
+                        Some(_) => Err(bad_step(name)),
+                    }
+                },
+            }
+        }
     }
-}
 
-impl<T> IsMutIndexable for T {
-    // dummy type
-    default type IndexType = u8;
+    /// Collection of leaf values, see [`LeafCollection`].
+    pub trait FieldOpsLeafCollection<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-    default const IS_MUT_INDEXABLE: bool = false;
+    impl<T: LeafCollection> FieldOpsLeafCollection<T> for &&&&Probe<T, usize> {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(|lua, ctx, path, vis| {
+                    build_indexed_proxy_leaf(lua, ctx, path, vis, T::METHODS).map(Value::Table)
+                }),
+                get:      |this, lua, base, name, steps| {
+                    match steps {
+                        [PathStep::Index(key)] => this.leaf_get(lua, base, key.clone()),
+                        _ => Err(bad_step(name)),
+                    }
+                },
+                set:      |this, lua, base, name, steps, value| {
+                    match steps {
+                        [PathStep::Index(key)] => this.leaf_set(lua, base, key.clone(), value),
+                        _ => Err(bad_step(name)),
+                    }
+                },
+                tostring: leaf_tostring,
+                keys:     |this, lua, base, name, steps| {
+                    if steps.is_empty() {
+                        this.leaf_keys(lua, base)
+                    } else {
+                        Err(bad_step(name))
+                    }
+                },
+                check:    no_check,
+                call:     |this, lua, base, name, steps, method, args| {
+                    if steps.is_empty() {
+                        this.call_method(lua, base, method, args)
+                    } else {
+                        Err(bad_step(name))
+                    }
+                },
+            }
+        }
+    }
 
-    default fn set_index_or_unreachable(&mut self, _index: usize, _val: Self::IndexType) {
-        unreachable!()
+    /// Same level as [`FieldOpsLeafCollection`], for the [`PlainField`]s.
+    pub trait FieldOpsPlain<T> {
+        fn field_ops(&self) -> FieldOps<T>;
     }
-}
 
-impl<T: IndexMut<usize>> IsMutIndexable for T
-where
-    <T as Index<usize>>::Output: Sized + 'static + Clone + mlua::IntoLua + mlua::FromLua,
-{
-    type IndexType = <T as Index<usize>>::Output;
+    impl<T: PlainField> FieldOpsPlain<T> for &&&&Probe<T, usize> {
+        fn field_ops(&self) -> FieldOps<T> {
+            leaf_field_ops()
+        }
+    }
 
-    const IS_MUT_INDEXABLE: bool = true;
+    /// User `Index<Idx>` written through [`NewIndex`].
+    pub trait FieldOpsNewIndex<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-    fn set_index_or_unreachable(&mut self, index: usize, val: Self::IndexType) {
-        self[index] = val;
+    impl<T, Idx> FieldOpsNewIndex<T> for &&&Probe<T, Idx>
+    where
+        T: Index<Idx, Output: Clone + IntoLua> + NewIndex<Key = Idx, Item: FromLua>,
+        Idx: FromLuaIndex + 'static,
+    {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(|lua, ctx, path, vis| {
+                    build_indexed_proxy_leaf(lua, ctx, path, vis, true).map(Value::Table)
+                }),
+                get:      index_get::<T, Idx>,
+                set:      |this, lua, base, name, steps, value| {
+                    let [PathStep::Index(key)] = steps else {
+                        return Err(bad_step(name));
+                    };
+                    let index = into_rust_index(Idx::from_lua_index(key.clone(), lua)?, base)?;
+                    this.new_index(index, FromLua::from_lua(value, lua)?)
+                },
+                tostring: leaf_tostring,
+                keys:     no_keys,
+                check:    no_check,
+                call:     no_methods,
+            }
+        }
     }
-}
 
-// Same but with [`NewIndex`]
-pub trait IsNewIndexable {
-    type Key: FromLua;
-    type Item: FromLua;
+    /// User `IndexMut<Idx>`.
+    pub trait FieldOpsIndexMut<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-    const IS_NEW_INDEXABLE: bool = false;
+    impl<T, Idx> FieldOpsIndexMut<T> for &&Probe<T, Idx>
+    where
+        T: IndexMut<Idx, Output: Clone + IntoLua + FromLua>,
+        Idx: FromLuaIndex + 'static,
+    {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(|lua, ctx, path, vis| {
+                    build_indexed_proxy_leaf(lua, ctx, path, vis, true).map(Value::Table)
+                }),
+                get:      index_get::<T, Idx>,
+                set:      |this, lua, base, name, steps, value| {
+                    let [PathStep::Index(key)] = steps else {
+                        return Err(bad_step(name));
+                    };
+                    let index = into_rust_index(Idx::from_lua_index(key.clone(), lua)?, base)?;
+                    let value = FromLua::from_lua(value, lua)?;
+                    user_index(name, || this[index] = value)
+                },
+                tostring: leaf_tostring,
+                keys:     no_keys,
+                check:    no_check,
+                call:     no_methods,
+            }
+        }
+    }
 
-    #[doc(hidden)]
-    fn set_index_or_unreachable(&mut self, _index: Self::Key, _val: Self::Item) {
-        unreachable!()
+    /// User `Index<Idx>`, read-only.
+    pub trait FieldOpsIndex<T> {
+        fn field_ops(&self) -> FieldOps<T>;
     }
-}
 
-impl<T> IsNewIndexable for T {
-    // dummy type
-    default type Item = u8;
-    default type Key = u8;
+    impl<T, Idx> FieldOpsIndex<T> for &Probe<T, Idx>
+    where
+        T: Index<Idx, Output: Clone + IntoLua>,
+        Idx: FromLuaIndex + 'static,
+    {
+        fn field_ops(&self) -> FieldOps<T> {
+            FieldOps {
+                proxy:    Some(|lua, ctx, path, vis| {
+                    build_indexed_proxy_leaf(lua, ctx, path, vis, true).map(Value::Table)
+                }),
+                get:      index_get::<T, Idx>,
+                set:      |_, _, _, name, _, _| Err(bad_step(name)),
+                tostring: leaf_tostring,
+                keys:     no_keys,
+                check:    no_check,
+                call:     no_methods,
+            }
+        }
+    }
 
-    default const IS_NEW_INDEXABLE: bool = false;
+    /// Leaf value, copied in and out as a whole.
+    pub trait FieldOpsLeaf<T> {
+        fn field_ops(&self) -> FieldOps<T>;
+    }
 
-    default fn set_index_or_unreachable(&mut self, _index: Self::Key, _val: Self::Item) {
-        unreachable!()
+    impl<T, Idx> FieldOpsLeaf<T> for Probe<T, Idx> {
+        fn field_ops(&self) -> FieldOps<T> {
+            leaf_field_ops()
+        }
     }
-}
 
-impl<T: NewIndex> IsNewIndexable for T
-where
-    <T as NewIndex>::Item: FromLua,
-    <T as NewIndex>::Key: FromLua,
-{
-    type Item = <T as NewIndex>::Item;
-    type Key = <T as NewIndex>::Key;
+    fn leaf_field_ops<T>() -> FieldOps<T> {
+        FieldOps {
+            proxy:    None,
+            get:      |_, _, _, name, _| Err(bad_step(name)),
+            set:      |_, _, _, name, _, _| Err(bad_step(name)),
+            tostring: leaf_tostring,
+            keys:     no_keys,
+            check:    no_check,
+            call:     |_, _, _, name, _, _, _| Err(bad_step(name)),
+        }
+    }
 
-    const IS_NEW_INDEXABLE: bool = true;
+    /// `IntoLua` conversion of a field value.
+    pub trait FieldIntoLuaDirect<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value>;
+    }
 
-    fn set_index_or_unreachable(&mut self, index: Self::Key, item: Self::Item) {
-        self.new_index(index.into_rust_index(), item);
+    impl<T: IntoLua> FieldIntoLuaDirect<T> for &&Probe<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value> {
+            T::into_lua
+        }
     }
-}
 
+    /// Conversion of the collections `mlua` has no conversion for, see
+    /// [`LuaCollection`](crate::LuaCollection).
+    pub trait FieldIntoLuaCollection<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value>;
+    }
 
-/// Converts indexes that are `usize` to `usize - 1`, else, returns `Self`
-trait IntoRustIndex {
-    fn into_rust_index(self) -> Self;
-}
+    impl<T: crate::LuaCollection> FieldIntoLuaCollection<T> for &Probe<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value> {
+            T::collection_into_lua
+        }
+    }
 
-// For all the types excepted `usize`, just self
-impl<T> IntoRustIndex for T {
-    default fn into_rust_index(self) -> Self {
-        self
+    /// No conversion, errors.
+    pub trait FieldIntoLuaNone<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value>;
     }
-}
 
-impl IntoRustIndex for usize {
-    fn into_rust_index(self) -> Self {
-        self - 1
+    impl<T> FieldIntoLuaNone<T> for Probe<T> {
+        fn field_into_lua(&self) -> fn(T, &Lua) -> mlua::Result<Value> {
+            |_, _| {
+                Err(mlua::Error::ToLuaConversionError {
+                    from:    std::any::type_name::<T>().to_owned(),
+                    to:      "value",
+                    message: Some("the type doesn't implement `IntoLua`".to_owned()),
+                })
+            }
+        }
     }
-}
 
+    /// Collection of `mlua_gen` elements, see [`CollectionProject::elems_from_lua`].
+    pub trait FieldFromLuaProject<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T>;
+    }
 
-/// Here to conditionnaly check if a type was generated by `mlua_gen`
-///
-/// The reason that we cannot just use `LuaBuilder` is because specialization won't work with
-/// generics inside `LuaBuilder` which are needed because of `R` and `E`. Therefore, there is
-/// [`mlua_gen::AutomaticImplWhenMluaGen`] which is automatically `impl`ed when using `#[mlua_gen]`
-#[doc(hidden)]
-pub trait IsMluaGenerated {
-    const IS_MLUA_GENERATED: bool = false;
-}
+    impl<T: CollectionProject> FieldFromLuaProject<T> for &&&Probe<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T> {
+            T::elems_from_lua
+        }
+    }
 
-impl<T> IsMluaGenerated for T {
-    default const IS_MLUA_GENERATED: bool = false;
-}
+    /// Same as [`FieldIntoLuaDirect`], from Lua.
+    pub trait FieldFromLuaDirect<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T>;
+    }
 
-impl<T: AutomaticImplWhenMluaGen> IsMluaGenerated for T {
-    const IS_MLUA_GENERATED: bool = true;
-}
+    impl<T: FromLua> FieldFromLuaDirect<T> for &&Probe<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T> {
+            T::from_lua
+        }
+    }
 
-/// Automatically `impl`ed when using `#[mlua_gen]`
-#[doc(hidden)]
-pub trait AutomaticImplWhenMluaGen {}
+    /// Same as [`FieldIntoLuaCollection`], from Lua.
+    pub trait FieldFromLuaCollection<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T>;
+    }
+
+    impl<T: crate::LuaCollection> FieldFromLuaCollection<T> for &Probe<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T> {
+            T::collection_from_lua
+        }
+    }
+
+    /// Same as [`FieldIntoLuaNone`], from Lua.
+    pub trait FieldFromLuaNone<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T>;
+    }
+
+    impl<T> FieldFromLuaNone<T> for Probe<T> {
+        fn field_from_lua(&self) -> fn(Value, &Lua) -> mlua::Result<T> {
+            |value, _| {
+                Err(mlua::Error::FromLuaConversionError {
+                    from:    value.type_name(),
+                    to:      std::any::type_name::<T>().to_owned(),
+                    message: Some("the type doesn't implement `FromLua`".to_owned()),
+                })
+            }
+        }
+    }
+
+    /// Lua copy of a `Clone` value, e.g. a proxy used as an operand or the `old` argument of
+    /// `on_change`.
+    pub trait LuaCopyDirect<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value>;
+    }
+
+    impl<T: Clone + IntoLua> LuaCopyDirect<T> for &&Probe<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value> {
+            |this, lua| this.clone().into_lua(lua)
+        }
+    }
+
+    /// Same as [`LuaCopyDirect`] for the collections of [`LuaCollection`](crate::LuaCollection).
+    pub trait LuaCopyCollection<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value>;
+    }
+
+    impl<T: Clone + crate::LuaCollection> LuaCopyCollection<T> for &Probe<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value> {
+            |this, lua| this.clone().collection_into_lua(lua)
+        }
+    }
+
+    /// No copy, errors.
+    pub trait LuaCopyNone<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value>;
+    }
+
+    impl<T> LuaCopyNone<T> for Probe<T> {
+        fn lua_copy(&self) -> fn(&T, &Lua) -> mlua::Result<Value> {
+            |_, _| {
+                Err(mlua::Error::runtime(format!(
+                    "empty path: `{}` is not `Clone`",
+                    std::any::type_name::<T>()
+                )))
+            }
+        }
+    }
+
+    /// `Display` of `__tostring`.
+    pub trait DisplayDirect<T> {
+        fn display(&self) -> Option<fn(&T) -> String>;
+    }
+
+    impl<T: Display> DisplayDirect<T> for &&Probe<T> {
+        fn display(&self) -> Option<fn(&T) -> String> {
+            Some(T::to_string)
+        }
+    }
+
+    /// `Debug` of `__tostring`, without `Display`.
+    pub trait DisplayDebug<T> {
+        fn display(&self) -> Option<fn(&T) -> String>;
+    }
+
+    impl<T: Debug> DisplayDebug<T> for &Probe<T> {
+        fn display(&self) -> Option<fn(&T) -> String> {
+            Some(|this| format!("{this:?}"))
+        }
+    }
+
+    /// Neither.
+    pub trait DisplayNone<T> {
+        fn display(&self) -> Option<fn(&T) -> String>;
+    }
+
+    impl<T> DisplayNone<T> for Probe<T> {
+        fn display(&self) -> Option<fn(&T) -> String> {
+            None
+        }
+    }
+
+    /// Levels of the arithmetic operators, used for `__add`, `__sub`, etc. Both operands are
+    /// `Self`, converted with [`operand`], or the right one is a number when the operator only
+    /// takes an `f64`, e.g. to scale a value.
+    macro_rules! arithmetic_probe {
+        ($probe:ident, $scalar:ident, $none:ident, $fn:ident, $op:ident, $method:ident) => {
+            pub trait $probe {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>>;
+            }
+
+            impl<T> $probe for &&Probe<T>
+            where
+                T: $op<Output = T> + Clone + FromLua + IntoLua + 'static,
+            {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>> {
+                    Some(|lua, lhs, rhs| {
+                        $op::$method(operand::<T>(lua, lhs)?, operand::<T>(lua, rhs)?).into_lua(lua)
+                    })
+                }
+            }
+
+            pub trait $scalar {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>>;
+            }
+
+            impl<T> $scalar for &Probe<T>
+            where
+                T: $op<f64, Output = T> + Clone + FromLua + IntoLua + 'static,
+            {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>> {
+                    Some(|lua, lhs, rhs| {
+                        $op::$method(operand::<T>(lua, lhs)?, f64::from_lua(rhs, lua)?).into_lua(lua)
+                    })
+                }
+            }
+
+            pub trait $none {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>>;
+            }
+
+            impl<T> $none for Probe<T> {
+                fn $fn(&self) -> Option<fn(&Lua, Value, Value) -> mlua::Result<Value>> {
+                    None
+                }
+            }
+        };
+    }
+
+    arithmetic_probe!(AddDirect, AddScalar, AddNone, add_operator, Add, add);
+    arithmetic_probe!(SubDirect, SubScalar, SubNone, sub_operator, Sub, sub);
+    arithmetic_probe!(MulDirect, MulScalar, MulNone, mul_operator, Mul, mul);
+    arithmetic_probe!(DivDirect, DivScalar, DivNone, div_operator, Div, div);
+    arithmetic_probe!(RemDirect, RemScalar, RemNone, rem_operator, Rem, rem);
+
+    /// Same but with [`Neg`], which only has one operand.
+    pub trait NegDirect {
+        fn neg_operator(&self) -> Option<fn(&Lua, Value) -> mlua::Result<Value>>;
+    }
+
+    impl<T> NegDirect for &Probe<T>
+    where
+        T: Neg<Output = T> + Clone + FromLua + IntoLua + 'static,
+    {
+        fn neg_operator(&self) -> Option<fn(&Lua, Value) -> mlua::Result<Value>> {
+            Some(|lua, value| (-operand::<T>(lua, value)?).into_lua(lua))
+        }
+    }
+
+    pub trait NegNone {
+        fn neg_operator(&self) -> Option<fn(&Lua, Value) -> mlua::Result<Value>>;
+    }
+
+    impl<T> NegNone for Probe<T> {
+        fn neg_operator(&self) -> Option<fn(&Lua, Value) -> mlua::Result<Value>> {
+            None
+        }
+    }
+
+    /// Same but with [`PartialEq`], used for `__eq`.
+    pub trait EqDirect {
+        fn eq_operator(&self) -> Option<fn(&Lua, (Value, Value)) -> mlua::Result<bool>>;
+    }
+
+    impl<T> EqDirect for &Probe<T>
+    where
+        T: PartialEq + Clone + FromLua + 'static,
+    {
+        fn eq_operator(&self) -> Option<fn(&Lua, (Value, Value)) -> mlua::Result<bool>> {
+            Some(|lua, (lhs, rhs)| Ok(operand::<T>(lua, lhs)? == operand::<T>(lua, rhs)?))
+        }
+    }
+
+    pub trait EqNone {
+        fn eq_operator(&self) -> Option<fn(&Lua, (Value, Value)) -> mlua::Result<bool>>;
+    }
+
+    impl<T> EqNone for Probe<T> {
+        fn eq_operator(&self) -> Option<fn(&Lua, (Value, Value)) -> mlua::Result<bool>> {
+            None
+        }
+    }
+
+    /// Same but with [`PartialOrd`], used for `__lt` and `__le`.
+    pub trait OrdDirect {
+        fn ord_operators(&self) -> Option<[fn(&Lua, (Value, Value)) -> mlua::Result<bool>; 2]>;
+    }
+
+    impl<T> OrdDirect for &Probe<T>
+    where
+        T: PartialOrd + Clone + FromLua + 'static,
+    {
+        fn ord_operators(&self) -> Option<[fn(&Lua, (Value, Value)) -> mlua::Result<bool>; 2]> {
+            Some([
+                |lua, (lhs, rhs)| Ok(operand::<T>(lua, lhs)? < operand::<T>(lua, rhs)?),
+                |lua, (lhs, rhs)| Ok(operand::<T>(lua, lhs)? <= operand::<T>(lua, rhs)?),
+            ])
+        }
+    }
+
+    pub trait OrdNone {
+        fn ord_operators(&self) -> Option<[fn(&Lua, (Value, Value)) -> mlua::Result<bool>; 2]>;
+    }
+
+    impl<T> OrdNone for Probe<T> {
+        fn ord_operators(&self) -> Option<[fn(&Lua, (Value, Value)) -> mlua::Result<bool>; 2]> {
+            None
+        }
+    }
+}
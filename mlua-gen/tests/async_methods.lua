@@ -0,0 +1,13 @@
+local store = Store.open(3)
+assert(store.version == 3)
+
+store:store("a", 1)
+store:store("b", 2)
+assert(store.version == 5)
+assert(store:len() == 2)
+
+assert(store:fetch("b") == 2)
+
+local ok, err = pcall(store.fetch, store, "c")
+assert(not ok)
+assert(string.find(tostring(err), "no entry named `c`", 1, true))
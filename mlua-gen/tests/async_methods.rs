@@ -0,0 +1,63 @@
+#![cfg(feature = "async")]
+
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    std::collections::HashMap,
+};
+
+#[derive(Debug)]
+struct NotFound(String);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no entry named `{}`", self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+#[mlua_gen(impl = [
+    async fetch(&self, String)?,
+    async store(&mut self, String, u32),
+    async open(u32),
+    len(&self),
+])]
+struct Store {
+    pub entries: HashMap<String, u32>,
+    pub version: u32,
+}
+
+impl Store {
+    async fn fetch(&self, key: String) -> Result<u32, NotFound> {
+        tokio::task::yield_now().await;
+        self.entries.get(&key).copied().ok_or(NotFound(key))
+    }
+
+    async fn store(&mut self, key: String, value: u32) {
+        tokio::task::yield_now().await;
+        self.entries.insert(key, value);
+        self.version += 1;
+    }
+
+    async fn open(version: u32) -> Self {
+        tokio::task::yield_now().await;
+        Self {
+            entries: HashMap::new(),
+            version,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[tokio::test]
+pub async fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Store::to_globals(&lua)?;
+
+    lua.load(include_str!("./async_methods.lua"))
+        .exec_async()
+        .await
+}
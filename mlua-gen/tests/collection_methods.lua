@@ -0,0 +1,88 @@
+-- `#` on proxies, see `PAIRS_METAMETHODS`
+local len = _VERSION ~= "Lua 5.1"
+
+-- Sequences of leaf values
+local counts = inventory.counts
+counts:push(5)
+assert(counts[4] == 5 and (not len or #counts == 4))
+assert(counts:pop() == 5)
+assert(not len or #counts == 3)
+counts:insert(1, 9)
+assert(counts[1] == 9 and counts[2] == 3)
+assert(counts:remove(2) == 3)
+assert(counts:contains(4))
+assert(not counts:contains(3))
+local keys = counts:keys()
+assert(#keys == 3 and keys[1] == 1 and keys[3] == 3)
+
+-- Writing just past the end appends, further out errors and leaves the sequence intact
+counts[4] = 2
+assert(counts[4] == 2 and (not len or #counts == 4))
+local ok, err = pcall(function() counts[6] = 1 end)
+assert(not ok and tostring(err):find("out of bounds"))
+assert(counts[5] == nil and counts[6] == nil and (not len or #counts == 4))
+assert(not pcall(function() counts:remove(10) end))
+counts:clear()
+assert(not len or #counts == 0)
+assert(counts:pop() == nil)
+for _, count in ipairs({ 1, 5, 9, 2 }) do
+    counts:push(count)
+end
+
+-- Sequences of `mlua_gen` values take tables or userdata
+local items = inventory.items
+items:push({ name = "shield", count = 2 })
+-- Userdata arguments are moved into the collection
+items:push(sword)
+assert(items[2].name == "shield" and (not len or #items == 3))
+items[3].count = 10
+assert(items:remove(3).count == 10)
+assert(not len or #items == 2)
+
+-- Maps: `insert` and `remove` return the previous value
+local named = inventory.named
+assert(named:insert("spare", { name = "sword", count = 1 }) == nil)
+assert(named:contains("spare"))
+assert(named:remove("main").name == "sword")
+assert(not named:contains("main"))
+named.extra = { name = "bow", count = 1 }
+assert(named.extra.name == "bow")
+named.extra = nil
+assert(named.extra == nil)
+local names = named:keys()
+assert(#names == 1 and names[1] == "spare")
+
+local scores = inventory.scores
+assert(scores:insert("bob", { name = "score", count = 6 }).count == 5)
+scores.carol = { name = "score", count = 8 }
+scores.alice = nil
+assert(scores:insert("bob", { name = "score", count = 5 }).count == 6)
+local keys = scores:keys()
+assert(#keys == 2 and keys[1] == "bob" and keys[2] == "carol")
+assert(not pcall(function() scores:push({ name = "score", count = 1 }) end))
+
+-- A map entry named like a method wins over the method, which comes back once the entry is
+-- removed: `contains` tells them apart
+assert(type(scores.keys) == "function" and not scores:contains("keys"))
+scores.keys = { name = "score", count = 1 }
+assert(scores.keys.count == 1 and scores:contains("keys"))
+scores.keys = nil
+assert(type(scores.keys) == "function")
+
+-- Collections inside enum variants
+local stack = inventory.slot.stack.items
+stack:push({ name = "arrow", count = 20 })
+assert(not len or #inventory.slot.stack.items == 1)
+
+-- Every mutation goes through `on_set`
+local before = hits()
+counts:push(0)
+counts:pop()
+named:clear()
+named:insert("spare", { name = "sword", count = 1 })
+assert(hits() == before + 4)
+
+-- Collections outside `set` have no mutating methods
+local locked = inventory.locked
+assert(locked:contains(7))
+assert(not pcall(function() locked:push(1) end))
@@ -0,0 +1,107 @@
+use {
+    mlua_gen::mlua_gen,
+    std::{
+        collections::{BTreeMap, HashMap},
+        sync::{
+            Arc,
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    },
+};
+
+static HITS: AtomicUsize = AtomicUsize::new(0);
+
+fn on_set_hook() {
+    HITS.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+enum Slot {
+    Empty,
+    Stack { items: Vec<Item> },
+}
+
+#[derive(Debug)]
+#[mlua_gen(get = *, set = [counts, items, named, scores, slot], on_set = crate::on_set_hook)]
+struct Inventory {
+    pub counts: Vec<u32>,
+    pub items:  Vec<Item>,
+    pub named:  HashMap<String, Item>,
+    pub scores: BTreeMap<String, Item>,
+    pub slot:   Slot,
+    pub locked: Vec<u32>,
+}
+
+fn score(count: u32) -> Item {
+    Item {
+        name: "score".to_owned(),
+        count,
+    }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    HITS.store(0, Ordering::Relaxed);
+
+    let lua = mlua::Lua::new();
+    let sword = Item {
+        name:  "sword".to_owned(),
+        count: 1,
+    };
+    let inventory = Arc::new(Mutex::new(Inventory {
+        counts: vec![3, 1, 4],
+        items:  vec![sword.clone()],
+        named:  HashMap::from([("main".to_owned(), sword.clone())]),
+        scores: BTreeMap::from([
+            ("alice".to_owned(), score(3)),
+            ("bob".to_owned(), score(5)),
+        ]),
+        slot:   Slot::Stack { items: vec![] },
+        locked: vec![7],
+    }));
+    lua.globals().set("inventory", inventory.clone())?;
+    lua.globals().set("sword", sword.clone())?;
+    lua.globals().set(
+        "hits",
+        lua.create_function(|_, ()| Ok(HITS.load(Ordering::Relaxed)))?,
+    )?;
+
+    lua.load(include_str!("./collection_methods.lua")).exec()?;
+
+    let inventory = inventory.lock().expect("not poisoned");
+    assert_eq!(inventory.counts, [1, 5, 9, 2]);
+    assert_eq!(
+        inventory.items,
+        [
+            sword.clone(),
+            Item {
+                name:  "shield".to_owned(),
+                count: 2,
+            }
+        ]
+    );
+    assert_eq!(inventory.named.len(), 1);
+    assert_eq!(inventory.named["spare"], sword);
+    assert_eq!(
+        inventory.scores,
+        BTreeMap::from([
+            ("bob".to_owned(), score(5)),
+            ("carol".to_owned(), score(8)),
+        ])
+    );
+    let Slot::Stack { items } = &inventory.slot else {
+        panic!("slot changed variant");
+    };
+    assert_eq!(items.len(), 1);
+    assert_eq!(inventory.locked, [7]);
+    Ok(())
+}
@@ -0,0 +1,14 @@
+local pig = Animal.Pig
+assert(pig:describe() == "a pig")
+
+local dog = Animal.Dog("Rex")
+assert(dog:describe() == "a dog named Rex")
+dog:rename("Max")
+assert(dog:describe() == "a dog named Max")
+assert(dog.dog[1] == "Max")
+
+local cat = Animal.Cat { name = "Tom", age = 3 }
+cat:rename("Felix")
+assert(cat.cat.name == "Felix")
+assert(cat:describe() == "a 3 years old cat named Felix")
+assert(cat:legs(4) == 4)
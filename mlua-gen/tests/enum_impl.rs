@@ -0,0 +1,40 @@
+use mlua_gen::{LuaBuilder, mlua_gen};
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    #[mlua_gen(impl = [describe(&self), rename(&mut self, String), legs(&self, u8)])]
+    enum Animal {
+        Pig,
+        Dog(String),
+        Cat { name: String, age: u8 },
+    }
+
+    impl Animal {
+        fn describe(&self) -> String {
+            match self {
+                Animal::Pig => "a pig".to_owned(),
+                Animal::Dog(name) => format!("a dog named {name}"),
+                Animal::Cat { name, age } => format!("a {age} years old cat named {name}"),
+            }
+        }
+
+        fn rename(&mut self, new_name: String) {
+            match self {
+                Animal::Pig => {},
+                Animal::Dog(name) | Animal::Cat { name, .. } => *name = new_name,
+            }
+        }
+
+        fn legs(&self, per_animal: u8) -> u8 {
+            match self {
+                Animal::Pig | Animal::Dog(_) | Animal::Cat { .. } => per_animal,
+            }
+        }
+    }
+
+    let lua = mlua::Lua::new();
+    Animal::to_globals(&lua)?;
+
+    lua.load(include_str!("./enum_impl.lua")).exec()?;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+local counter = Shape.Counter { count = 1, label = "clicks", secret = 7 }
+assert(counter.counter.count == 1)
+assert(counter.counter.label == "clicks")
+assert(not pcall(function() return counter.counter.secret end))
+
+counter.counter.count = 5
+assert(counter.counter.count == 5)
+assert(not pcall(function() counter.counter.label = "other" end))
+
+local snapshot = counter.counter
+assert(snapshot.count == 5)
+
+-- Replacing the whole variant would write `label` and `secret`
+assert(not pcall(function() counter.counter = { count = 0, label = "", secret = 0 } end))
+
+local pair = Shape.Pair("left", 2)
+assert(pair.pair[1] == "left")
+assert(not pcall(function() return pair.pair[2] end))
+
+pair.pair[2] = 3
+assert(not pcall(function() pair.pair[1] = "right" end))
+assert(pair.pair[1] == "left")
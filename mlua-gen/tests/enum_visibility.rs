@@ -0,0 +1,21 @@
+use mlua_gen::{LuaBuilder, mlua_gen};
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    #[mlua_gen(get = [Counter::count, label, 0], set = [count, 1])]
+    #[allow(dead_code)] // `secret` is only written from Lua
+    enum Shape {
+        Counter {
+            count:  u32,
+            label:  String,
+            secret: u8,
+        },
+        Pair(String, u32),
+    }
+
+    let lua = mlua::Lua::new();
+    Shape::to_globals(&lua)?;
+
+    lua.load(include_str!("./enum_visibility.lua")).exec()?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+local account = Account { balance = 100, frozen = false }
+
+-- `Ok` values are returned as is
+assert(account:withdraw(30) == 70)
+assert(account:remaining() == 70)
+
+-- `Err` values are raised
+local ok, err = pcall(account.withdraw, account, 100)
+assert(not ok)
+assert(string.find(tostring(err), "insufficient funds, missing 30", 1, true))
+assert(account:remaining() == 70)
+
+account.frozen = true
+ok, err = pcall(account.withdraw, account, 1)
+assert(not ok)
+assert(string.find(tostring(err), "account is frozen", 1, true))
+
+-- Static functions too
+assert(Account.open(5).balance == 5)
+ok, err = pcall(Account.open, 0)
+assert(not ok)
+assert(string.find(tostring(err), "missing 1", 1, true))
+
+-- With `errors = nil`, `nil, err` is returned instead
+local amount, err = Amount.from_signed(150)
+assert(amount.cents == 150)
+assert(err == nil)
+
+amount, err = Amount.from_signed(-1)
+assert(amount == nil)
+assert(string.find(tostring(err), "out of range", 1, true))
@@ -0,0 +1,78 @@
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    std::fmt,
+};
+
+#[derive(Debug)]
+enum BankError {
+    Insufficient { missing: u32 },
+    Frozen,
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insufficient { missing } => write!(f, "insufficient funds, missing {missing}"),
+            Self::Frozen => write!(f, "account is frozen"),
+        }
+    }
+}
+
+impl std::error::Error for BankError {}
+
+#[mlua_gen(impl = [withdraw(&mut self, u32)?, remaining(&self), open(u32)?])]
+struct Account {
+    pub balance: u32,
+    pub frozen:  bool,
+}
+
+impl Account {
+    fn withdraw(&mut self, amount: u32) -> Result<u32, BankError> {
+        if self.frozen {
+            return Err(BankError::Frozen);
+        }
+        self.balance = self.balance.checked_sub(amount).ok_or_else(|| {
+            BankError::Insufficient {
+                missing: amount - self.balance,
+            }
+        })?;
+        Ok(self.balance)
+    }
+
+    const fn remaining(&self) -> u32 {
+        self.balance
+    }
+
+    const fn open(deposit: u32) -> Result<Self, BankError> {
+        if deposit == 0 {
+            Err(BankError::Insufficient { missing: 1 })
+        } else {
+            Ok(Self {
+                balance: deposit,
+                frozen:  false,
+            })
+        }
+    }
+}
+
+#[mlua_gen(impl = [from_signed(i64)?], errors = nil)]
+struct Amount {
+    pub cents: u32,
+}
+
+impl Amount {
+    fn from_signed(cents: i64) -> Result<Self, std::num::TryFromIntError> {
+        Ok(Self {
+            cents: cents.try_into()?,
+        })
+    }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Account::to_globals(&lua)?;
+    Amount::to_globals(&lua)?;
+
+    lua.load(include_str!("./fallible_methods.lua")).exec()
+}
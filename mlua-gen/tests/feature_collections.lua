@@ -0,0 +1,20 @@
+-- `SmallVec` behaves like `Vec`
+local counts = stash.counts
+counts[2] = 20
+counts:push(3)
+assert(#counts == 3 and counts[3] == 3)
+
+stash.items[1].count = 7
+stash.items:push({ name = "bow", count = 1 })
+assert(#stash.items == 2)
+
+-- `IndexMap` behaves like `HashMap`
+local prices = stash.prices
+assert(prices.bow == 5)
+stash.prices = { sword = 12 }
+assert(stash.prices.bow == nil)
+
+stash.named.main.count = 3
+stash.named:insert("spare", { name = "bow", count = 1 })
+assert(stash.named.spare.name == "bow")
+assert(stash.named:remove("spare").name == "bow")
@@ -0,0 +1,56 @@
+#![cfg(all(feature = "smallvec", feature = "indexmap"))]
+
+use {
+    indexmap::IndexMap,
+    mlua_gen::mlua_gen,
+    smallvec::{SmallVec, smallvec},
+    std::sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub count: u32,
+}
+
+#[derive(Debug)]
+#[mlua_gen]
+struct Stash {
+    pub counts: SmallVec<[u32; 2]>,
+    pub items:  SmallVec<[Item; 2]>,
+    pub prices: IndexMap<String, u32>,
+    pub named:  IndexMap<String, Item>,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let stash = Arc::new(Mutex::new(Stash {
+        counts: smallvec![1, 2],
+        items:  smallvec![Item {
+            name:  "sword".to_owned(),
+            count: 1,
+        }],
+        prices: IndexMap::from([("sword".to_owned(), 10), ("bow".to_owned(), 5)]),
+        named:  IndexMap::from([(
+            "main".to_owned(),
+            Item {
+                name:  "axe".to_owned(),
+                count: 2,
+            },
+        )]),
+    }));
+    lua.globals().set("stash", stash.clone())?;
+
+    lua.load(include_str!("./feature_collections.lua")).exec()?;
+
+    let stash = stash.lock().expect("not poisoned");
+    assert_eq!(stash.counts.as_slice(), [1, 20, 3]);
+    assert_eq!(stash.items[0].count, 7);
+    assert_eq!(stash.items[1].name, "bow");
+    assert_eq!(stash.prices.get("sword"), Some(&12));
+    assert_eq!(stash.named["main"].count, 3);
+    assert!(!stash.named.contains_key("spare"));
+    Ok(())
+}
@@ -0,0 +1,24 @@
+-- `FromLua` uses the renamed keys, skipped fields are `Default`ed.
+local p = Player { displayName = "eden", password = "", stats = Stats { hp = 3, level = 2 } }
+assert(p.displayName == "eden")
+assert(not pcall(function() return p.display_name end))
+
+-- Skipped: neither readable nor writable.
+assert(not pcall(function() return p.cache end))
+assert(not pcall(function() p.cache = {} end))
+
+-- Write-only.
+p.password = "secret"
+assert(not pcall(function() return p.password end))
+
+-- Read-only, through a nested proxy.
+assert(p.stats.level == 2)
+assert(p.stats.hp == 3)
+assert(not pcall(function() p.stats.level = 9 end))
+assert(p.stats.level == 2)
+
+-- Renamed fields on a shared root propagate to Rust.
+shared.displayName = "renamed"
+shared.password = "hunter2"
+shared.stats.hp = 42
+assert(not pcall(function() shared.stats.level = 9 end))
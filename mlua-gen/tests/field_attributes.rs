@@ -0,0 +1,55 @@
+//! Field-level `#[mlua(rename, skip, readonly, writeonly)]`.
+
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    std::sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Stats {
+    #[mlua(rename = "hp")]
+    pub health: u32,
+    #[mlua(readonly)]
+    level:      u8,
+}
+
+#[derive(Debug)]
+#[mlua_gen(get = *, set = *)]
+struct Player {
+    #[mlua(rename = "displayName")]
+    display_name: String,
+    #[mlua(skip)]
+    cache:        Vec<u8>,
+    #[mlua(writeonly)]
+    password:     String,
+    stats:        Stats,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Player::to_globals(&lua)?;
+    Stats::to_globals(&lua)?;
+
+    let player = Arc::new(Mutex::new(Player {
+        display_name: "root".to_owned(),
+        cache:        vec![1, 2, 3],
+        password:     String::new(),
+        stats:        Stats {
+            health: 10,
+            level:  1,
+        },
+    }));
+    lua.globals().set("shared", player.clone())?;
+
+    lua.load(include_str!("./field_attributes.lua")).exec()?;
+
+    let player = player.lock().unwrap();
+    assert_eq!(player.display_name, "renamed");
+    assert_eq!(player.password, "hunter2");
+    assert_eq!(player.cache, vec![1, 2, 3]);
+    assert_eq!(player.stats.health, 42);
+    assert_eq!(player.stats.level, 1);
+    Ok(())
+}
@@ -0,0 +1,66 @@
+local function rejected(f, message)
+    local ok, err = pcall(f)
+    assert(not ok)
+    assert(string.find(tostring(err), message, 1, true), tostring(err))
+end
+
+-- Ranges
+rejected(function() player.hp = 101 end, "invalid value for `Player.hp`: expected a value in 0..=100")
+assert(player.hp == 10)
+player.hp = 100
+assert(player.hp == 100)
+
+-- Lengths and custom validators
+rejected(function() player.name = "far too long" end, "expected a length of at most 8")
+rejected(function() player.name = "t k r" end, "invalid value for `Player.name`: expected an alphanumeric name")
+assert(player.name == "tkr")
+player.name = "Ferris"
+assert(player.name == "Ferris")
+
+-- Nested fields, through proxies
+player.settings.volume = 3.5
+assert(player.settings.volume == 1.0)
+rejected(function() player.settings.fps = 0 end, "invalid value for `Player.settings.fps`: expected a value in 1..")
+player.settings.fps = 30
+assert(player.settings.fps == 30)
+
+-- Tuple structs
+rejected(function() percent[1] = -1 end, "invalid value for `Percent[1]`")
+assert(percent[1] == 0)
+percent[1] = 42
+assert(percent[1] == 42)
+
+-- Whole values, converted from tables
+rejected(function() player.settings = { volume = 0.5, fps = 0 } end, "invalid value for `Player.settings.fps`")
+assert(player.settings.fps == 30)
+player.settings = { volume = 2, fps = 60 }
+assert(player.settings.volume == 1.0)
+assert(player.settings.fps == 60)
+
+-- Elements of collections, named by their Lua index
+rejected(function() player.list[1] = { volume = 0.5, fps = 0 } end, "invalid value for `Player.list[1].fps`")
+rejected(function() player.list[1].fps = 0 end, "invalid value for `Player.list[1].fps`")
+rejected(function() player.list = { { volume = 0.5, fps = 1 }, { volume = 0.5, fps = 0 } } end, "invalid value for `Player.list[2].fps`")
+assert(player.list[1].fps == 60)
+
+-- Same for the elements added by the collection methods, or by writing past the end
+rejected(function() player.list:push({ volume = 0.5, fps = 0 }) end, "invalid value for `Player.list[2].fps`")
+rejected(function() player.list:insert(1, { volume = 0.5, fps = 0 }) end, "invalid value for `Player.list[1].fps`")
+rejected(function() player.list[2] = { volume = 0.5, fps = 0 } end, "invalid value for `Player.list[2].fps`")
+assert(#player.list:keys() == 1)
+assert(player.list[1].fps == 60)
+
+-- Builders
+local function settings(fps) return { volume = 0.5, fps = fps } end
+rejected(function() Player { name = "tkr", hp = 200, settings = settings(1), list = {} } end, "invalid value for `Player.hp`")
+rejected(function() Player { name = "tkr", hp = 20, settings = settings(0), list = {} } end, "invalid value for `Player.settings.fps`")
+rejected(function() Player { name = "tkr", hp = 20, settings = settings(1), list = { settings(0) } } end, "invalid value for `Player.list[1].fps`")
+rejected(function() Percent(101) end, "invalid value for `Percent[1]`")
+local built = Player { name = "tkr", hp = 20, settings = { volume = -1, fps = 1 }, list = {} }
+assert(built.settings.volume == 0.0)
+assert(Percent(42)[1] == 42)
+
+-- `on_change` sees the clamped value, directly and through proxies
+mixer.volume = 3.5
+mixer.settings.volume = -2.5
+assert(mixer.volume == 1.0 and mixer.settings.volume == 0.0)
@@ -0,0 +1,92 @@
+use {
+    mlua::Value,
+    mlua_gen::{LuaBuilder, PathStep, mlua_gen},
+    std::sync::{Arc, Mutex},
+};
+
+static VOLUMES: Mutex<Vec<f64>> = Mutex::new(Vec::new());
+
+fn check_name(name: &str) -> Result<(), &'static str> {
+    if name.chars().all(char::is_alphanumeric) {
+        Ok(())
+    } else {
+        Err("expected an alphanumeric name")
+    }
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Settings {
+    #[mlua(clamp = 0.0..=1.0)]
+    pub volume: f32,
+    #[mlua(range = 1..)]
+    pub fps:    u32,
+}
+
+#[mlua_gen]
+struct Player {
+    #[mlua(max_len = 8, validate = crate::check_name)]
+    pub name:     String,
+    #[mlua(range = 0..=100)]
+    pub hp:       u8,
+    pub settings: Settings,
+    pub list:     Vec<Settings>,
+}
+
+fn record_volume(_: &Mixer, _: &[PathStep], _: &Value, new: &Value) {
+    if let Some(volume) = new.as_f64() {
+        VOLUMES.lock().expect("not poisoned").push(volume);
+    }
+}
+
+#[mlua_gen(on_change = crate::record_volume)]
+struct Mixer {
+    #[mlua(clamp = 0.0..=1.0)]
+    pub volume:   f64,
+    pub settings: Settings,
+}
+
+#[mlua_gen]
+struct Percent(#[mlua(range = 0..=100)] pub i32);
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let player = Arc::new(Mutex::new(Player {
+        name:     "tkr".to_owned(),
+        hp:       10,
+        settings: Settings {
+            volume: 0.5,
+            fps:    60,
+        },
+        list:     vec![Settings {
+            volume: 0.5,
+            fps:    60,
+        }],
+    }));
+    lua.globals().set("player", player.clone())?;
+    lua.globals().set("percent", Percent(0))?;
+    lua.globals().set(
+        "mixer",
+        Mixer {
+            volume:   0.5,
+            settings: Settings {
+                volume: 0.5,
+                fps:    60,
+            },
+        },
+    )?;
+    Player::to_globals(&lua)?;
+    Percent::to_globals(&lua)?;
+
+    lua.load(include_str!("./field_constraints.lua")).exec()?;
+
+    let player = player.lock().expect("not poisoned");
+    assert_eq!(player.name, "Ferris");
+    assert_eq!(player.hp, 100);
+    assert!((player.settings.volume - 1.0).abs() < f32::EPSILON);
+    assert_eq!(player.settings.fps, 60);
+    assert_eq!(player.list.len(), 1);
+    assert_eq!(*VOLUMES.lock().expect("not poisoned"), [1.0, 0.0]);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+-- A bounded type parameter writes through
+outer.inner.x = 5
+assert(outer.inner.x == 5)
+local inner = outer.inner
+inner.x = inner.x
+assert(outer.inner.x == 5)
+
+-- Same in a collection
+outer.items[2].x = 5
+assert(outer.items[2].x == 5)
+
+-- Unbounded ones are plain values, written whole
+loose.inner = { x = 6 }
+assert(loose.inner.x == 6)
+loose.items[2] = { x = 6 }
+assert(loose.items[2].x == 6)
+
+-- `nightly` specializes on them, writing through
+if nightly then
+    loose.inner.x = 7
+    loose.items[2].x = 7
+else
+    loose.inner = { x = 7 }
+    loose.items[2] = { x = 7 }
+end
+assert(loose.inner.x == 7 and loose.items[2].x == 7)
+
+assert(loose_int.inner == 3)
+loose_int.inner = 4
+assert(loose_int.inner == 4)
+loose_int.items[1] = 10
+assert(loose_int.items[1] == 10)
@@ -0,0 +1,58 @@
+use {
+    mlua::{FromLua, IntoLua},
+    mlua_gen::{MluaGenProject, mlua_gen},
+};
+
+#[derive(Clone, Default)]
+#[mlua_gen]
+struct Inner {
+    pub x: i32,
+}
+
+/// Bound by `MluaGenProject`, `inner` and `items` are proxied like concrete fields
+#[mlua_gen]
+struct Outer<T: MluaGenProject + FromLua + Clone + IntoLua + Send + Sync + 'static> {
+    pub inner: T,
+    pub items: Vec<T>,
+}
+
+/// Without the bound, `T` is a plain value, unless `nightly` sees that it is an `mlua_gen` type
+#[mlua_gen]
+struct Loose<T: FromLua + Clone + IntoLua + Send + Sync + 'static> {
+    pub inner: T,
+    pub items: Vec<T>,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let outer = lua.create_userdata(Outer {
+        inner: Inner::default(),
+        items: vec![Inner::default(), Inner::default()],
+    })?;
+    lua.globals().set("outer", &outer)?;
+
+    let loose = lua.create_userdata(Loose {
+        inner: Inner::default(),
+        items: vec![Inner::default(), Inner::default()],
+    })?;
+    lua.globals().set("loose", &loose)?;
+    lua.globals().set(
+        "loose_int",
+        Loose {
+            inner: 3,
+            items: vec![1, 2],
+        },
+    )?;
+    lua.globals().set("nightly", cfg!(feature = "nightly"))?;
+
+    lua.load(include_str!("./generic_nested.lua")).exec()?;
+
+    let outer = outer.borrow::<Outer<Inner>>()?;
+    assert_eq!(outer.inner.x, 5);
+    assert_eq!(outer.items[1].x, 5);
+    let loose = loose.borrow::<Loose<Inner>>()?;
+    assert_eq!(loose.inner.x, 7);
+    assert_eq!(loose.items[1].x, 7);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+-- `#` and `pairs` on proxies, see `PAIRS_METAMETHODS`
+local len = _VERSION ~= "Lua 5.1"
+local iterate = len and not _VERSION:find("^Luau")
+
+-- Tuple fields start at 0
+assert(pair[0] == 2 and pair[1] == "two")
+assert(not pcall(function() return pair[2] end))
+pair[0] = 3
+assert(pair[0] == 3)
+
+local inner = level.pair
+inner[0] = 10
+inner[1] = "ten"
+assert(level.pair[0] == 10)
+if iterate then
+    local positions = {}
+    for key in pairs(inner) do
+        positions[#positions + 1] = key
+    end
+    assert(#positions == 2 and positions[1] == 0 and positions[2] == 1)
+end
+
+-- Whole tuples are tables keyed from 0 too
+level.pair = { [0] = 11, [1] = "eleven" }
+assert(level.pair[0] == 11 and level.pair[1] == "eleven")
+
+-- Tuple variants: proxies, whole-variant tables and snapshots
+local segment = level.shape.segment
+assert(segment[0] == 1 and segment[1] == 2)
+assert(not pcall(function() return segment[2] end))
+level.shape.segment = { [0] = 3, [1] = 4 }
+assert(level.shape.segment[0] == 3 and level.shape.segment[1] == 4)
+shape.segment = { [0] = 5, [1] = 6 }
+assert(shape.segment[0] == 5 and shape.segment[1] == 6)
+level.shape = { segment = { [0] = 7, [1] = 8 } }
+
+-- Leaf sequences read and write from 0, one past the end appends
+local counts = level.counts
+assert(counts[0] == 3 and counts[2] == 4)
+assert(counts[3] == nil)
+counts[0] = 9
+counts[3] = 7
+assert(counts[3] == 7 and (not len or #counts == 4))
+assert(not pcall(function() counts[5] = 1 end))
+local keys = counts:keys()
+assert(keys[1] == 0 and keys[4] == 3)
+if iterate then
+    local seen = 0
+    for key, value in pairs(counts) do
+        assert(counts[key] == value)
+        seen = seen + 1
+    end
+    assert(seen == 4)
+end
+
+-- Methods take and report positions from 0 too
+counts:insert(0, 6)
+assert(counts[0] == 6 and counts[1] == 9)
+assert(counts:remove(0) == 6)
+assert(not pcall(function() counts:remove(4) end))
+counts:push(8)
+assert(counts[4] == 8)
+assert(counts:contains(8))
+
+-- Sequences of `mlua_gen` values
+local items = level.items
+assert(items[0].name == "sword")
+items[0].name = "axe"
+items[1] = { name = "bow", slots = {} }
+assert(items[1].name == "bow" and (not len or #items == 2))
+
+-- Each type keeps its own base: `Item` stays 1-based
+assert(items[0].slots[1] == 1)
+items[0].slots[2] = 5
@@ -0,0 +1,76 @@
+use {
+    mlua::Value,
+    mlua_gen::{PathStep, mlua_gen},
+    std::sync::{Arc, Mutex},
+};
+
+static SNAPSHOTS: Mutex<Vec<(Option<u32>, Option<u32>)>> = Mutex::new(vec![]);
+
+// Snapshot of the `Shape::Segment` replaced through a proxy, keyed from 0 as well
+fn record_segment(_: &Level, steps: &[PathStep], old: &Value, _: &Value) {
+    if let (Some(PathStep::Variant("segment")), Value::Table(old)) = (steps.last(), old) {
+        SNAPSHOTS
+            .lock()
+            .expect("not poisoned")
+            .push((old.get(0).expect("u32"), old.get(1).expect("u32")));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen(index_base = 0)]
+struct Pair(pub u32, pub String);
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub slots: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen(index_base = 0)]
+enum Shape {
+    Point,
+    Segment(u32, u32),
+}
+
+#[derive(Debug)]
+#[mlua_gen(index_base = 0, on_change = crate::record_segment)]
+struct Level {
+    pub counts: Vec<u32>,
+    pub items:  Vec<Item>,
+    pub pair:   Pair,
+    pub shape:  Shape,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let level = Arc::new(Mutex::new(Level {
+        counts: vec![3, 1, 4],
+        items:  vec![Item {
+            name:  "sword".to_owned(),
+            slots: vec![1, 2],
+        }],
+        pair:   Pair(1, "one".to_owned()),
+        shape:  Shape::Segment(1, 2),
+    }));
+    lua.globals().set("level", level.clone())?;
+    lua.globals().set("pair", Pair(2, "two".to_owned()))?;
+    lua.globals().set("shape", Shape::Point)?;
+
+    lua.load(include_str!("./index_base.lua")).exec()?;
+
+    let level = level.lock().expect("not poisoned");
+    assert_eq!(level.counts, [9, 1, 4, 7, 8]);
+    assert_eq!(level.items.len(), 2);
+    assert_eq!(level.items[0].name, "axe");
+    assert_eq!(level.items[0].slots, [1, 5]);
+    assert_eq!(level.pair, Pair(11, "eleven".to_owned()));
+    assert_eq!(level.shape, Shape::Segment(7, 8));
+    assert_eq!(
+        *SNAPSHOTS.lock().expect("not poisoned"),
+        [(Some(1), Some(2))]
+    );
+    Ok(())
+}
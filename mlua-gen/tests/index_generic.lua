@@ -0,0 +1,26 @@
+-- Keys are converted to the field's `Idx`, as is
+local names = world.names
+assert(names[7] == "alice")
+assert(names[42] == "bob")
+names[42] = "carol"
+assert(world.names[42] == "carol")
+
+-- Tuple indices are read from a sequence
+local grid = world.grid
+assert(grid[{ 1, 0 }] == 1)
+assert(grid[{ 0, 1 }] == 2)
+grid[{ 0, 1 }] = 9
+assert(world.grid[{ 0, 1 }] == 9)
+assert(not pcall(function() return grid[{ 0 }] end))
+
+-- A panic of the user `Index`, like on an unknown key, is a Lua error
+local ok, err = pcall(function() return names[99] end)
+assert(not ok and tostring(err):find("known id"))
+assert(not pcall(function() names[99] = "dave" end))
+assert(not pcall(function() return grid[{ 5, 5 }] end))
+assert(names[7] == "alice")
+
+-- `usize` indices stay 1-based
+local scores = world.scores
+assert(scores[1] == 10 and scores[3] == nil)
+scores[1] = 11
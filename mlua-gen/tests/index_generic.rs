@@ -0,0 +1,117 @@
+use {
+    mlua_gen::mlua_gen,
+    std::{
+        ops::{Index, IndexMut},
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EntityId(u32);
+
+impl mlua::FromLua for EntityId {
+    fn from_lua(value: mlua::Value, lua: &mlua::Lua) -> mlua::Result<Self> {
+        Ok(Self(u32::from_lua(value, lua)?))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Names(Vec<(EntityId, String)>);
+
+impl Index<EntityId> for Names {
+    type Output = String;
+
+    fn index(&self, id: EntityId) -> &String {
+        &self
+            .0
+            .iter()
+            .find(|(key, _)| *key == id)
+            .expect("known id")
+            .1
+    }
+}
+
+impl IndexMut<EntityId> for Names {
+    fn index_mut(&mut self, id: EntityId) -> &mut String {
+        &mut self
+            .0
+            .iter_mut()
+            .find(|(key, _)| *key == id)
+            .expect("known id")
+            .1
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Grid {
+    width: u32,
+    cells: Vec<u8>,
+}
+
+impl Index<(u32, u32)> for Grid {
+    type Output = u8;
+
+    fn index(&self, (x, y): (u32, u32)) -> &u8 {
+        &self.cells[usize::try_from(y * self.width + x).expect("fits")]
+    }
+}
+
+impl IndexMut<(u32, u32)> for Grid {
+    fn index_mut(&mut self, (x, y): (u32, u32)) -> &mut u8 {
+        &mut self.cells[usize::try_from(y * self.width + x).expect("fits")]
+    }
+}
+
+/// Whole-field reads and writes move the containers as plain userdata.
+macro_rules! plain_user_data {
+    ($ty:ty) => {
+        impl mlua::UserData for $ty {}
+
+        impl mlua::FromLua for $ty {
+            fn from_lua(value: mlua::Value, _lua: &mlua::Lua) -> mlua::Result<Self> {
+                match value {
+                    mlua::Value::UserData(ud) => Ok(ud.borrow::<Self>()?.clone()),
+                    _ => Err(mlua::Error::runtime("expected a userdata")),
+                }
+            }
+        }
+    };
+}
+
+plain_user_data!(Names);
+plain_user_data!(Grid);
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct World {
+    #[mlua(index = EntityId)]
+    pub names:  Names,
+    #[mlua(index = (u32, u32))]
+    pub grid:   Grid,
+    pub scores: Vec<u32>,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let world = Arc::new(Mutex::new(World {
+        names:  Names(vec![
+            (EntityId(7), "alice".to_owned()),
+            (EntityId(42), "bob".to_owned()),
+        ]),
+        grid:   Grid {
+            width: 2,
+            cells: vec![0, 1, 2, 3],
+        },
+        scores: vec![10, 20],
+    }));
+    lua.globals().set("world", world.clone())?;
+
+    lua.load(include_str!("./index_generic.lua")).exec()?;
+
+    let world = world.lock().expect("not poisoned");
+    assert_eq!(world.names[EntityId(42)], "carol");
+    assert_eq!(world.grid.cells, [0, 1, 9, 3]);
+    assert_eq!(world.scores, [11, 20]);
+    Ok(())
+}
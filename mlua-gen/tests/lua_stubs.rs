@@ -0,0 +1,100 @@
+use mlua_gen::{LuaStub, LuaStubs, mlua_gen};
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    #[mlua_gen(get = *, set = [int], impl = [uwu(&self, i32, i32), new()])]
+    struct Test {
+        name:  String,
+        int:   u32,
+        tags:  Vec<String>,
+        other: Option<Pair>,
+    }
+
+    #[derive(Clone)]
+    #[mlua_gen]
+    struct Pair(pub(crate) f64, pub(crate) bool);
+
+    impl Test {
+        fn uwu(&self, a: i32, b: i32) -> i32 {
+            i32::try_from(self.int).unwrap() + a + b
+        }
+
+        fn new() -> Test {
+            Test {
+                name:  String::new(),
+                int:   0,
+                tags:  vec![],
+                other: None,
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    #[mlua_gen(index_base = 0)]
+    struct ZeroPair(pub(crate) f64, pub(crate) bool);
+
+    #[mlua_gen(index_base = 0)]
+    enum Edge {
+        Open,
+        Span(u32, String),
+    }
+
+    #[mlua_gen]
+    enum Animal {
+        Pig,
+        Dog(String),
+        Cat { name: String, age: u8 },
+    }
+
+    let class = Test::lua_class_stub();
+    assert!(class.starts_with("---@class Test\n"));
+    assert!(class.contains("---@field name string\n"));
+    assert!(class.contains("---@field int integer\n"));
+    assert!(class.contains("---@field tags string[]\n"));
+    assert!(class.contains("---@field other Pair?\n"));
+    assert!(class.contains("---@field uwu fun(self: Test, arg1: integer, arg2: integer): any\n"));
+
+    let global = Test::lua_global_stub("Test");
+    assert!(global.contains("---@class TestBuilder\n"));
+    assert!(global.contains(
+        "---@overload fun(fields: { name: string, int: integer, tags: string[], other: Pair? }): Test\n"
+    ));
+    assert!(global.contains("---@return any\nfunction Test.new() end\n"));
+
+    assert!(Pair::lua_class_stub().contains("---@field [2] boolean\n"));
+    assert!(
+        Pair::lua_global_stub("Pair")
+            .contains("---@overload fun(arg1: number, arg2: boolean): Pair\n")
+    );
+
+    // Tuple fields are keyed from the `index_base` of their type
+    let class = ZeroPair::lua_class_stub();
+    assert!(class.contains("---@field [0] number\n") && class.contains("---@field [1] boolean\n"));
+    assert!(Edge::lua_class_stub().contains("---@field span { [0]: integer, [1]: string }?\n"));
+
+    let class = Animal::lua_class_stub();
+    assert!(class.contains("---@field pig true?\n"));
+    assert!(class.contains("---@field dog { [1]: string }?\n"));
+    assert!(class.contains("---@field cat { name: string, age: integer }?\n"));
+    let global = Animal::lua_global_stub("Creature");
+    assert!(global.contains("---@field Pig Animal\n"));
+    assert!(global.contains("---@field Dog fun(arg1: string): Animal\n"));
+    assert!(global.contains("Creature = {}\n"));
+
+    let mut stubs = LuaStubs::new();
+    stubs
+        .add::<Test>()
+        .add::<Pair>()
+        .add_as::<Animal>("Creature");
+    let path = std::env::temp_dir().join("mlua_gen_lua_stubs.d.lua");
+    stubs.write(&path)?;
+    let written = std::fs::read_to_string(&path)?;
+    assert!(written.starts_with("---@meta\n"));
+    assert_eq!(written, stubs.to_string());
+
+    // Stubs are plain Lua, they must at least load.
+    let lua = mlua::Lua::new();
+    lua.load(written).exec()?;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+-- Generalized iteration goes through `__iter`
+local fields = {}
+for key in inventory do
+    table.insert(fields, key)
+end
+table.sort(fields)
+assert(table.concat(fields, ",") == "counts,items")
+
+local total = 0
+for position, count in inventory.counts do
+    assert(inventory.counts[position] == count)
+    total += count
+end
+assert(total == 7 and #inventory.counts == 2)
+
+for _, item in inventory.items do
+    item.count += 1
+end
@@ -0,0 +1,39 @@
+#![cfg(feature = "luau")]
+
+use {
+    mlua_gen::mlua_gen,
+    std::sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub count: u32,
+}
+
+#[derive(Debug)]
+#[mlua_gen]
+struct Inventory {
+    pub items:  Vec<Item>,
+    pub counts: Vec<u32>,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let inventory = Arc::new(Mutex::new(Inventory {
+        items:  vec![Item {
+            name:  "sword".to_owned(),
+            count: 1,
+        }],
+        counts: vec![3, 4],
+    }));
+    lua.globals().set("inventory", inventory.clone())?;
+
+    lua.load(include_str!("./luau_iteration.lua")).exec()?;
+
+    let inventory = inventory.lock().expect("not poisoned");
+    assert_eq!(inventory.items[0].count, 2);
+    Ok(())
+}
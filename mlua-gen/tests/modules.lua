@@ -0,0 +1,21 @@
+-- Dotted names in the globals
+local player = _G.game.entities.Player { name = "Ada" }
+assert(player.name == "Ada")
+assert(_G.game.entities.Player.default().name == "")
+assert(_G.game.Faction.Red.red == true)
+assert(_G.Player == nil)
+
+-- Registered in the environment, not in the globals
+assert(Player { name = "Eve" }.name == "Eve")
+assert(Faction.Red.red == true)
+
+-- `game` of the environment, not the one of the globals
+assert(game.Hero { name = "Zed" }.name == "Zed")
+assert(game.entities == nil)
+assert(_G.game.Hero == nil)
+
+local world = require("world")
+assert(world.Player { name = "Bob" }.name == "Bob")
+assert(world.Team.Blue.blue == true)
+assert(world.Faction == nil)
+assert(require("world") == world)
@@ -0,0 +1,43 @@
+use mlua_gen::{LuaBuilder, Module, mlua_gen, to_lua};
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    #[mlua_gen(get = *, set = *, impl = [default()])]
+    #[derive(Default)]
+    struct Player {
+        name: String,
+    }
+
+    #[mlua_gen]
+    enum Faction {
+        Red,
+        Blue,
+    }
+
+    let lua = mlua::Lua::new();
+
+    // Dotted names create the intermediate tables
+    Player::to_globals_as(&lua, "game.entities.Player")?;
+    Faction::to_globals_as(&lua, "game.Faction")?;
+    lua.load("game.count = 1").exec()?;
+    assert!(Faction::to_globals_as(&lua, "game.count.Faction").is_err());
+
+    // Sandboxed environment, falling back to the globals
+    let env = lua.create_table()?;
+    let fallback = lua.create_table()?;
+    fallback.set("__index", lua.globals())?;
+    env.set_metatable(Some(fallback));
+    to_lua!(lua => env, Player, Faction);
+    Player::to_table_as(&lua, &env, "game.Hero")?;
+
+    let mut module = Module::new("world");
+    module
+        .add(Player::to_table)
+        .add(|lua, table| Faction::to_table_as(lua, table, "Team"));
+    module.preload(&lua)?;
+
+    lua.load(include_str!("./modules.lua"))
+        .set_environment(env)
+        .exec()?;
+    Ok(())
+}
@@ -0,0 +1,14 @@
+-- Top level field
+world.tick = 1
+
+-- Nested fields, through proxies
+world.hero.hp = 45
+world.units[1].hp = 3
+world.hero.tags[1] = "bold"
+
+-- Tuple struct
+point[2] = 5
+
+-- Enum variants
+mode.moving = { speed = 2 }
+mode.moving.speed = 3
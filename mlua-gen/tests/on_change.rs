@@ -0,0 +1,126 @@
+use {
+    mlua::Value,
+    mlua_gen::{LuaBuilder, PathStep, mlua_gen},
+    std::sync::{Arc, Mutex},
+};
+
+static CHANGES: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+fn describe(steps: &[PathStep], old: &Value, new: &Value) -> String {
+    let path = steps
+        .iter()
+        .filter_map(|step| {
+            match step {
+                PathStep::Field(name) | PathStep::Variant(name) => Some((*name).to_owned()),
+                PathStep::Tuple(idx) => Some(idx.to_string()),
+                PathStep::Index(key) => Some(format!("[{}]", key.to_string().unwrap_or_default())),
+                PathStep::Unwrap => None,
+                step => Some(format!("{step:?}")),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".");
+    format!("{path}: {} -> {}", show(old), show(new))
+}
+
+/// `tostring` of `value`, listing the fields of a table.
+fn show(value: &Value) -> String {
+    match value {
+        Value::Table(table) => {
+            let mut fields = table
+                .pairs::<String, Value>()
+                .map(|pair| {
+                    let (key, value) = pair.expect("string keys");
+                    format!("{key} = {}", show(&value))
+                })
+                .collect::<Vec<_>>();
+            fields.sort();
+            format!("{{ {} }}", fields.join(", "))
+        },
+        value => value.to_string().unwrap_or_default(),
+    }
+}
+
+fn record_world(world: &World, steps: &[PathStep], old: &Value, new: &Value) {
+    let change = describe(steps, old, new);
+    CHANGES
+        .lock()
+        .expect("not poisoned")
+        .push(format!("{change} (tick {})", world.tick));
+}
+
+fn record_point(_: &Point, steps: &[PathStep], old: &Value, new: &Value) {
+    CHANGES
+        .lock()
+        .expect("not poisoned")
+        .push(describe(steps, old, new));
+}
+
+// The old variant is `nil` since `Mode` isn't `Clone`
+fn record_mode(_: &Mode, steps: &[PathStep], old: &Value, new: &Value) {
+    CHANGES
+        .lock()
+        .expect("not poisoned")
+        .push(describe(steps, old, new));
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Unit {
+    pub hp:   u32,
+    pub tags: Vec<String>,
+}
+
+#[mlua_gen(on_change = crate::record_world)]
+struct World {
+    pub tick:  u32,
+    pub units: Vec<Unit>,
+    pub hero:  Unit,
+}
+
+#[mlua_gen(on_change = crate::record_point)]
+struct Point(pub i32, pub i32);
+
+#[mlua_gen(on_change = crate::record_mode)]
+#[allow(dead_code)] // variants are reachable through Lua, not Rust
+enum Mode {
+    Idle,
+    Moving { speed: u32 },
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Unit::to_globals(&lua)?;
+
+    let world = Arc::new(Mutex::new(World {
+        tick:  0,
+        units: vec![Unit {
+            hp:   10,
+            tags: vec![],
+        }],
+        hero:  Unit {
+            hp:   50,
+            tags: vec!["brave".to_owned()],
+        },
+    }));
+    lua.globals().set("world", world.clone())?;
+    lua.globals().set("point", Point(1, 2))?;
+    lua.globals().set("mode", Mode::Moving { speed: 1 })?;
+
+    lua.load(include_str!("./on_change.lua")).exec()?;
+
+    assert_eq!(
+        *CHANGES.lock().expect("not poisoned"),
+        [
+            "tick: 0 -> 1 (tick 1)",
+            "hero.hp: 50 -> 45 (tick 1)",
+            "units.[1].hp: 10 -> 3 (tick 1)",
+            "hero.tags.[1]: brave -> bold (tick 1)",
+            "1: 2 -> 5",
+            "moving: nil -> { speed = 2 }",
+            "moving.speed: 2 -> 3",
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,45 @@
+local a = Vec2(1, 2)
+local b = Vec2(3, 5)
+
+local sum = a + b
+assert(sum[1] == 4 and sum[2] == 7)
+local diff = b - a
+assert(diff[1] == 2 and diff[2] == 3)
+local neg = -a
+assert(neg[1] == -1 and neg[2] == -2)
+
+assert(a == Vec2(1, 2))
+assert(a ~= b)
+assert(a < b)
+assert(a <= Vec2(1, 2))
+assert(not (b < a))
+
+-- Only implemented traits are registered
+assert(not pcall(function() return a * b end))
+assert(not pcall(function() return Money { cents = 1 } - Money { cents = 1 } end))
+
+local price = Money { cents = 250 }
+assert((price * 2).cents == 500 and (price * 0.5).cents == 125)
+-- `Mul<f64>` only takes a number on the right
+assert(not pcall(function() return price * price end))
+assert(not pcall(function() return 2 * price end))
+assert("price: " .. price == "price: $2.50")
+assert(price .. "!" == "$2.50!")
+
+-- Proxies resolve to the value they point at
+-- Lua only calls `__eq` when both sides are tables or both are userdata, so
+-- `wallet.balance == Money { cents = 1050 }` is false: compare two proxies or two userdata
+assert(wallet.balance == wallet.balance)
+-- Comparing a table with a userdata, see `PAIRS_METAMETHODS`
+if _VERSION ~= "Lua 5.1" and not _VERSION:find("^Luau") then
+    assert(wallet.balance <= Money { cents = 1050 } and wallet.balance >= Money { cents = 1050 })
+    assert(wallet.balance > price)
+end
+assert((wallet.balance + price).cents == 1300)
+assert((wallet.balance * 2).cents == 2100)
+assert(wallet.position + a == Vec2(2, 4))
+assert(-wallet.position == Vec2(-1, -2))
+assert(wallet.position == wallet.position)
+assert("left: " .. wallet.balance == "left: $10.50")
+
+wallet.balance = wallet.balance + Money { cents = 50 }
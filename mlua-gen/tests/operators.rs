@@ -0,0 +1,95 @@
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    std::{
+        fmt,
+        ops::{Add, Mul, Neg, Sub},
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[mlua_gen]
+struct Vec2(pub f64, pub f64);
+
+impl Add for Vec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(-self.0, -self.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[mlua_gen]
+struct Money {
+    pub cents: f64,
+}
+
+impl Add for Money {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            cents: self.cents + rhs.cents,
+        }
+    }
+}
+
+impl Mul<f64> for Money {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self {
+            cents: self.cents * rhs,
+        }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${:.2}", self.cents / 100.)
+    }
+}
+
+#[mlua_gen]
+struct Wallet {
+    pub balance:  Money,
+    pub position: Vec2,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Vec2::to_globals(&lua)?;
+    Money::to_globals(&lua)?;
+
+    let wallet = Arc::new(Mutex::new(Wallet {
+        balance:  Money { cents: 1050. },
+        position: Vec2(1., 2.),
+    }));
+    lua.globals().set("wallet", wallet.clone())?;
+
+    lua.load(include_str!("./operators.lua")).exec()?;
+
+    assert_eq!(
+        wallet.lock().expect("not poisoned").balance,
+        Money { cents: 1100. }
+    );
+    Ok(())
+}
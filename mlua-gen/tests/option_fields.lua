@@ -0,0 +1,34 @@
+-- `Some` is a live proxy
+unit.stats.hp = 15
+unit.stats.buffs:push(3)
+assert(unit.stats.hp == 15 and unit.stats.buffs[1] == 3)
+-- `#` on proxies, see `PAIRS_METAMETHODS`
+if _VERSION ~= "Lua 5.1" then
+    assert(#unit.stats.buffs == 1)
+end
+
+-- `None` reads as nil, and a table makes it `Some`
+assert(unit.spare == nil)
+unit.spare = { hp = 5, buffs = {} }
+local spare = unit.spare
+spare.hp = 6
+assert(unit.spare.hp == 6)
+
+-- A proxy whose value became `None` errors
+unit.spare = nil
+assert(unit.spare == nil)
+local ok, err = pcall(function() return spare.hp end)
+assert(not ok and tostring(err):find("value became nil under proxy"))
+assert(not pcall(function() spare.hp = 1 end))
+
+-- Collections of options
+assert(unit.slots[1].hp == 1)
+assert(unit.slots[2] == nil)
+unit.slots[1].hp = 2
+unit.slots[2] = { hp = 7, buffs = {} }
+assert(unit.slots[2].hp == 7)
+
+-- Options of leaf values are plain values
+assert(unit.level == nil)
+unit.level = 4
+assert(unit.level == 4)
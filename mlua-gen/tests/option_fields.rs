@@ -0,0 +1,51 @@
+use {
+    mlua_gen::mlua_gen,
+    std::sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen]
+struct Stats {
+    pub hp:    u32,
+    pub buffs: Vec<u32>,
+}
+
+#[derive(Debug)]
+#[mlua_gen]
+struct Unit {
+    pub stats: Option<Stats>,
+    pub spare: Option<Stats>,
+    pub slots: Vec<Option<Stats>>,
+    pub level: Option<u32>,
+}
+
+const fn stats(hp: u32) -> Stats {
+    Stats { hp, buffs: vec![] }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let unit = Arc::new(Mutex::new(Unit {
+        stats: Some(stats(10)),
+        spare: None,
+        slots: vec![Some(stats(1)), None],
+        level: None,
+    }));
+    lua.globals().set("unit", unit.clone())?;
+
+    lua.load(include_str!("./option_fields.lua")).exec()?;
+
+    let unit = unit.lock().expect("not poisoned");
+    assert_eq!(
+        unit.stats,
+        Some(Stats {
+            hp:    15,
+            buffs: vec![3],
+        })
+    );
+    assert_eq!(unit.spare, None);
+    assert_eq!(unit.slots, [Some(stats(2)), Some(stats(7))]);
+    assert_eq!(unit.level, Some(4));
+    Ok(())
+}
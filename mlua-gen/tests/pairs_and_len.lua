@@ -0,0 +1,72 @@
+-- Lua 5.1 and LuaJIT ignore `#` and `pairs` on proxies, Luau goes through `__iter` with its
+-- generalized iteration, see `PAIRS_METAMETHODS`
+if _VERSION == "Lua 5.1" then
+    return
+end
+
+local function each(t, iterate)
+    if _VERSION:find("^Luau") then
+        return t
+    end
+    return (iterate or pairs)(t)
+end
+
+local function sorted_keys(t)
+    local keys = {}
+    for k in each(t) do
+        table.insert(keys, tostring(k))
+    end
+    table.sort(keys)
+    return table.concat(keys, ",")
+end
+
+-- Struct userdata lists its readable fields
+assert(sorted_keys(sword) == "count,name")
+for k, v in each(sword) do
+    assert(sword[k] == v)
+end
+assert(sorted_keys(inventory) == "counts,items,named,origin,slot")
+
+-- Collections of `mlua_gen` values
+local items = inventory.items
+assert(#items == 2)
+local names = {}
+for i, item in each(items, ipairs) do
+    names[i] = item.name
+end
+assert(table.concat(names, ",") == "sword,arrow")
+for i, item in each(items) do
+    if item.name == "arrow" then
+        item.count = item.count + 1
+    end
+end
+assert(inventory.items[3] == nil)
+
+assert(sorted_keys(inventory.named) == "main,quiver")
+for key, item in each(inventory.named) do
+    assert(inventory.named[key].name == item.name)
+end
+
+-- Collections of leaf values
+local counts = inventory.counts
+assert(#counts == 3)
+local sum = 0
+for _, count in each(counts, ipairs) do
+    sum = sum + count
+end
+assert(sum == 8)
+for i, count in each(counts) do
+    counts[i] = count * 2
+end
+assert(counts[4] == nil)
+
+-- Nested proxies
+assert(sorted_keys(inventory.items[1]) == "count,name")
+assert(sorted_keys(inventory.origin) == "1,2")
+for i, v in each(inventory.origin) do
+    assert(inventory.origin[i] == v)
+end
+assert(sorted_keys(inventory.slot) == "filled")
+assert(sorted_keys(inventory.slot.filled) == "item")
+assert(sorted_keys(inventory.slot.filled.item) == "count,name")
+assert(sorted_keys(empty) == "empty")
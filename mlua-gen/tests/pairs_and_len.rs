@@ -0,0 +1,72 @@
+use {
+    mlua_gen::mlua_gen,
+    std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Point(pub i32, pub i32);
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+enum Slot {
+    Empty,
+    Filled { item: Item },
+}
+
+#[mlua_gen]
+struct Inventory {
+    pub items:  Vec<Item>,
+    pub counts: Vec<u32>,
+    pub named:  HashMap<String, Item>,
+    pub origin: Point,
+    pub slot:   Slot,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let sword = Item {
+        name:  "sword".to_owned(),
+        count: 1,
+    };
+    let arrow = Item {
+        name:  "arrow".to_owned(),
+        count: 20,
+    };
+    let inventory = Arc::new(Mutex::new(Inventory {
+        items:  vec![sword.clone(), arrow.clone()],
+        counts: vec![3, 1, 4],
+        named:  HashMap::from([
+            ("main".to_owned(), sword.clone()),
+            ("quiver".to_owned(), arrow),
+        ]),
+        origin: Point(4, 2),
+        slot:   Slot::Filled {
+            item: sword.clone(),
+        },
+    }));
+    lua.globals().set("inventory", inventory.clone())?;
+    lua.globals().set("sword", sword)?;
+    lua.globals().set("empty", Slot::Empty)?;
+
+    lua.load(include_str!("./pairs_and_len.lua")).exec()?;
+
+    if cfg!(any(feature = "lua51", feature = "luajit")) {
+        return Ok(());
+    }
+    let inventory = inventory.lock().expect("not poisoned");
+    assert_eq!(inventory.items[1].count, 21);
+    assert_eq!(inventory.counts, [6, 2, 8]);
+    Ok(())
+}
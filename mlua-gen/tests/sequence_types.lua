@@ -0,0 +1,51 @@
+-- `#` and `ipairs` on proxies, see `PAIRS_METAMETHODS`
+local len = _VERSION ~= "Lua 5.1"
+local iterate = len and not _VERSION:find("^Luau")
+
+-- Arrays and boxed slices have a fixed length
+local slots = shelf.slots
+assert(slots[2] == 2 and (not len or #slots == 3))
+slots[2] = 20
+assert(shelf.slots[2] == 20)
+assert(slots[4] == nil)
+assert(not pcall(function() slots[4] = 1 end))
+assert(not pcall(function() slots:push(4) end))
+
+local boxed = shelf.boxed
+assert(not len or #boxed == 2)
+boxed[2] = 50
+assert(not pcall(function() boxed[3] = 1 end))
+
+-- `VecDeque` grows at the back
+local queue = shelf.queue
+queue[2] = 70
+queue:push(8)
+queue:push(9)
+assert(not len or #queue == 4)
+assert(queue:pop() == 9)
+if iterate then
+    local seen = {}
+    for i, value in ipairs(queue) do
+        seen[i] = value
+    end
+    assert(#seen == 3 and seen[3] == 8)
+end
+
+-- Elements generated by `mlua_gen` are proxied
+shelf.pair[1].count = 10
+shelf.pair[2] = { name = "mace", count = 2 }
+assert(shelf.pair[2].name == "mace")
+assert(not pcall(function() shelf.pair[3] = { name = "bow", count = 1 } end))
+
+shelf.line:push({ name = "spear", count = 5 })
+assert(shelf.line[2].name == "spear" and (not len or #shelf.line == 2))
+
+shelf.frozen[1].count = 40
+assert(not pcall(function() shelf.frozen[2] = { name = "bow", count = 1 } end))
+
+-- Whole-field replacement goes through plain tables
+local saved = shelf.queue
+shelf.queue = { 1, 2 }
+assert(shelf.queue[2] == 2 and (not len or #shelf.queue == 2))
+shelf.queue = { 6, 70, 8 }
+assert(saved[3] == 8)
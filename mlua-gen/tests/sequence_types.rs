@@ -0,0 +1,57 @@
+use {
+    mlua_gen::mlua_gen,
+    std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[mlua_gen]
+struct Item {
+    pub name:  String,
+    pub count: u32,
+}
+
+#[derive(Debug)]
+#[mlua_gen]
+struct Shelf {
+    pub slots:  [u32; 3],
+    pub boxed:  Box<[u32]>,
+    pub queue:  VecDeque<u32>,
+    pub pair:   [Item; 2],
+    pub line:   VecDeque<Item>,
+    pub frozen: Box<[Item]>,
+}
+
+fn item(name: &str, count: u32) -> Item {
+    Item {
+        name: name.to_owned(),
+        count,
+    }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let shelf = Arc::new(Mutex::new(Shelf {
+        slots:  [1, 2, 3],
+        boxed:  vec![4, 5].into_boxed_slice(),
+        queue:  VecDeque::from([6, 7]),
+        pair:   [item("sword", 1), item("shield", 2)],
+        line:   VecDeque::from([item("bow", 3)]),
+        frozen: vec![item("axe", 4)].into_boxed_slice(),
+    }));
+    lua.globals().set("shelf", shelf.clone())?;
+
+    lua.load(include_str!("./sequence_types.lua")).exec()?;
+
+    let shelf = shelf.lock().expect("not poisoned");
+    assert_eq!(shelf.slots, [1, 20, 3]);
+    assert_eq!(*shelf.boxed, [4, 50]);
+    assert_eq!(shelf.queue, [6, 70, 8]);
+    assert_eq!(shelf.pair, [item("sword", 10), item("mace", 2)]);
+    assert_eq!(shelf.line, [item("bow", 3), item("spear", 5)]);
+    assert_eq!(*shelf.frozen, [item("axe", 40)]);
+    Ok(())
+}
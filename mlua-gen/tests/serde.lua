@@ -0,0 +1,25 @@
+-- Constructors honour `#[serde(default)]`, `#[serde(rename)]` and `#[serde(flatten)]`
+local window = Window { title = "main", fullscreen = true }
+assert(window.title == "main")
+assert(window.width == 800)
+assert(window.fullscreen_mode == true)
+assert(window.theme.dark == false)
+
+local dark = Window { title = "dark", width = 300, fullscreen = false, dark = true }
+assert(dark.width == 300)
+assert(dark.theme.dark == true)
+
+-- So do plain tables passed as arguments
+assert(Tools.describe({ title = "plain", fullscreen = false }) == "plain 800 false false")
+assert(Tools.describe({ title = "flat", fullscreen = true, dark = true }) == "flat 800 true true")
+
+-- Enums use serde's representation
+assert(Tools.total_area({ { square = { side = 2 } }, { rect = { width = 2, height = 3 } } }) == 10)
+
+assert(not pcall(Tools.describe, { fullscreen = true }))
+
+-- The field constraints hold for what serde builds, nested values included
+local ok, err = pcall(Tools.describe, { title = "tiny", width = 10, fullscreen = false })
+assert(not ok and string.find(tostring(err), "invalid value for `Window.width`", 1, true), tostring(err))
+assert(not pcall(Window, { title = "tiny", width = 10, fullscreen = false }))
+assert(Window { title = "sharp", fullscreen = false, contrast = 50 }.theme.contrast == 10)
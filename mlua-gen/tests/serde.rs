@@ -0,0 +1,80 @@
+#![cfg(feature = "serde")]
+
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    serde::Deserialize,
+};
+
+#[derive(Clone, Deserialize)]
+#[mlua_gen(serde)]
+struct Window {
+    pub title:           String,
+    #[serde(default = "default_width")]
+    #[mlua(range = 100..=4000)]
+    pub width:           u32,
+    #[serde(rename = "fullscreen")]
+    pub fullscreen_mode: bool,
+    #[serde(flatten)]
+    pub theme:           Theme,
+}
+
+const fn default_width() -> u32 {
+    800
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[mlua_gen(serde)]
+struct Theme {
+    #[serde(default)]
+    pub dark:     bool,
+    #[serde(default)]
+    #[mlua(clamp = 0..=10)]
+    pub contrast: u8,
+}
+
+#[derive(Clone, Deserialize)]
+#[mlua_gen(serde, impl = [area(&self)])]
+#[serde(rename_all = "lowercase")]
+enum Shape {
+    Square { side: u32 },
+    Rect { width: u32, height: u32 },
+}
+
+impl Shape {
+    fn area(&self) -> u32 {
+        match self {
+            Shape::Square { side } => side * side,
+            Shape::Rect { width, height } => width * height,
+        }
+    }
+}
+
+#[mlua_gen(impl = [describe(Window), total_area(Vec::<Shape>)])]
+struct Tools;
+
+impl Tools {
+    fn describe(window: Window) -> String {
+        let Window {
+            title,
+            width,
+            fullscreen_mode,
+            theme,
+        } = window;
+        format!("{title} {width} {fullscreen_mode} {}", theme.dark)
+    }
+
+    fn total_area(shapes: Vec<Shape>) -> u32 {
+        shapes.into_iter().map(|shape| shape.area()).sum()
+    }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Window::to_globals(&lua)?;
+    Shape::to_globals(&lua)?;
+    Tools::to_globals(&lua)?;
+
+    lua.load(include_str!("./serde.lua")).exec()?;
+    Ok(())
+}
@@ -0,0 +1,62 @@
+-- `#` and `pairs` on proxies, see `PAIRS_METAMETHODS`
+local len = _VERSION ~= "Lua 5.1"
+local iterate = len and not _VERSION:find("^Luau")
+
+local tags = tagged.tags
+
+-- Membership
+assert(tags.red == true)
+assert(tags.blue == true)
+assert(tags.green == nil)
+assert(not len or #tags == 2)
+
+-- Sets have no methods: elements named like one are plain members
+assert(tags.remove == nil and tags.contains == nil)
+tags.remove = true
+assert(tags.remove == true and (not len or #tags == 3))
+tags.remove = nil
+assert(tags.remove == nil)
+
+-- `true` inserts, `nil` removes
+tags.green = true
+tags.red = nil
+tags.red = nil
+assert(tags.red == nil and tags.green == true)
+assert(not len or #tags == 2)
+assert(not pcall(function() tags.yellow = 1 end))
+assert(tags.yellow == nil)
+
+-- `pairs` yields each element with `true`, `ipairs` nothing
+if iterate then
+    local seen = {}
+    for value, present in pairs(tags) do
+        assert(present == true)
+        table.insert(seen, value)
+    end
+    table.sort(seen)
+    assert(table.concat(seen, ",") == "blue,green")
+    for _ in ipairs(tags) do
+        error("sets have no positions")
+    end
+end
+
+-- Ordered sets iterate in order
+local ids = tagged.ids
+assert(ids[2] == true and ids[4] == nil)
+if iterate then
+    local order = {}
+    for id in pairs(ids) do
+        table.insert(order, id)
+    end
+    assert(table.concat(order, ",") == "1,2,3")
+end
+
+-- Whole-set replacement
+tagged.ids = { [2] = true, [4] = true }
+assert(tagged.ids[4] == true and tagged.ids[1] == nil and (not len or #tagged.ids == 2))
+
+-- Sets in tuple structs
+assert(flags[1].on == true)
+flags[1].on = nil
+flags[1].off = true
+assert(flags[1].off == true and (not len or #flags[1] == 1))
@@ -0,0 +1,43 @@
+use {
+    mlua_gen::mlua_gen,
+    std::{
+        collections::{BTreeSet, HashSet},
+        sync::{Arc, Mutex},
+    },
+};
+
+#[mlua_gen]
+struct Tagged {
+    pub tags: HashSet<String>,
+    pub ids:  BTreeSet<u32>,
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Flags(pub BTreeSet<String>);
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let tagged = Arc::new(Mutex::new(Tagged {
+        tags: HashSet::from(["red".to_owned(), "blue".to_owned()]),
+        ids:  BTreeSet::from([3, 1, 2]),
+    }));
+    let flags = Arc::new(Mutex::new(Flags(BTreeSet::from(["on".to_owned()]))));
+    lua.globals().set("tagged", tagged.clone())?;
+    lua.globals().set("flags", flags.clone())?;
+
+    lua.load(include_str!("./sets.lua")).exec()?;
+
+    let tagged = tagged.lock().expect("not poisoned");
+    assert_eq!(
+        tagged.tags,
+        HashSet::from(["blue".to_owned(), "green".to_owned()])
+    );
+    assert_eq!(tagged.ids, BTreeSet::from([2, 4]));
+    assert_eq!(
+        flags.lock().expect("not poisoned").0,
+        BTreeSet::from(["off".to_owned()])
+    );
+    Ok(())
+}
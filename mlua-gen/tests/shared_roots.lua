@@ -0,0 +1,8 @@
+assert(game.score == 0)
+assert(game.player.name == "tkr")
+
+game.score = game.score + 10
+game.player.hp = game.player.hp - 3
+
+local player = game.player
+assert(player.hp == 7)
@@ -0,0 +1,90 @@
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone)]
+#[mlua_gen::mlua_gen]
+struct Game {
+    pub score:  u32,
+    pub player: Player,
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen::mlua_gen]
+struct Player {
+    pub name: String,
+    pub hp:   u8,
+}
+
+fn game() -> Game {
+    Game {
+        score:  0,
+        player: Player {
+            name: "tkr".to_owned(),
+            hp:   10,
+        },
+    }
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let game = Arc::new(RwLock::new(game()));
+    lua.globals().set("game", game.clone())?;
+
+    lua.load(include_str!("./shared_roots.lua")).exec()?;
+
+    let game = game.read().expect("not poisoned");
+    assert_eq!(game.score, 10);
+    assert_eq!(game.player.hp, 7);
+    Ok(())
+}
+
+#[cfg(feature = "parking_lot")]
+#[test]
+pub fn test_parking_lot() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let game = Arc::new(parking_lot::RwLock::new(game()));
+    lua.globals().set("game", game.clone())?;
+
+    lua.load(include_str!("./shared_roots.lua")).exec()?;
+
+    assert_eq!(game.read().player.hp, 7);
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+pub fn test_tokio() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let root = Arc::new(tokio::sync::RwLock::new(game()));
+    lua.globals()
+        .set("game", mlua_gen::root_proxy::<Game, _>(&lua, root.clone())?)?;
+
+    lua.load(include_str!("./shared_roots.lua")).exec()?;
+    assert_eq!(root.blocking_read().player.hp, 7);
+
+    // Outside of a runtime, a held lock is waited for
+    let mutex = Arc::new(tokio::sync::Mutex::new(game()));
+    lua.globals().set(
+        "locked",
+        mlua_gen::root_proxy::<Game, _>(&lua, mutex.clone())?,
+    )?;
+    let guard = mutex.clone().try_lock_owned().expect("not locked");
+    let holder = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(guard);
+    });
+    lua.load("locked.score = locked.score + 1").exec()?;
+    holder.join().expect("holder thread");
+    assert_eq!(mutex.blocking_lock().score, 1);
+
+    // Inside one, it is only tried and reported instead of blocking
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .map_err(mlua::Error::external)?;
+    let _runtime = runtime.enter();
+    let guard = mutex.try_lock().expect("not locked");
+    assert!(lua.load("return locked.score").exec().is_err());
+    drop(guard);
+    assert_eq!(lua.load("return locked.score").eval::<u32>()?, 1);
+    Ok(())
+}
@@ -0,0 +1,23 @@
+-- Variants, functions and consts share the table of the enum, under its custom name
+assert(Heading == nil)
+assert(Compass.North.north == true)
+assert(Compass.north().north == true)
+assert(Compass.from_degrees(720).north == true)
+assert(Compass.from_degrees(450).degrees[1] == 90)
+assert(Compass.Degrees(45).degrees[1] == 45)
+assert(Compass.FULL_TURN == 360)
+
+-- A unit struct stays its value, holding its functions and consts, and is built again when called
+assert(type(Origin) == "userdata")
+assert(type(Origin()) == "userdata")
+assert(type(Origin.zero()) == "userdata")
+assert(Origin.LABEL == "origin")
+
+-- Constructor through `__call`, functions in the same table
+local player = Player { name = "Ada", heading = Compass.Degrees(90) }
+assert(player.name == "Ada")
+assert(player.heading.degrees[1] == 90)
+assert(Player.MAX_NAME == 16)
+local default = Player.default()
+assert(default.name == "")
+assert(default.heading.north == true)
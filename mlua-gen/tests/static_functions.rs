@@ -0,0 +1,67 @@
+use mlua_gen::{LuaBuilder, LuaStub, mlua_gen};
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    #[derive(Clone, Copy, Default)]
+    #[mlua_gen(impl = [north(), from_degrees(u16)], consts = [FULL_TURN])]
+    enum Heading {
+        #[default]
+        North,
+        Degrees(u16),
+    }
+
+    impl Heading {
+        const FULL_TURN: u16 = 360;
+
+        fn north() -> Self {
+            Self::North
+        }
+
+        fn from_degrees(degrees: u16) -> Self {
+            if degrees.is_multiple_of(Self::FULL_TURN) {
+                Self::North
+            } else {
+                Self::Degrees(degrees % Self::FULL_TURN)
+            }
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    #[mlua_gen(impl = [zero()], consts = [LABEL])]
+    struct Origin;
+
+    impl Origin {
+        const LABEL: &'static str = "origin";
+
+        fn zero() -> Self {
+            Self
+        }
+    }
+
+    #[derive(Default)]
+    #[mlua_gen(get = *, set = *, impl = [default()], consts = [MAX_NAME])]
+    struct Player {
+        name:    String,
+        heading: Heading,
+    }
+
+    impl Player {
+        const MAX_NAME: usize = 16;
+    }
+
+    let lua = mlua::Lua::new();
+    Heading::to_globals_as(&lua, "Compass")?;
+    Origin::to_globals(&lua)?;
+    Player::to_globals(&lua)?;
+
+    lua.load(include_str!("./static_functions.lua")).exec()?;
+
+    assert!(
+        Heading::lua_global_stub("Compass").contains("function Compass.from_degrees(arg1) end\n")
+    );
+    let _: Origin = lua.globals().get("Origin")?;
+    assert!(Origin::lua_class_stub().contains("---@overload fun(): Origin\n"));
+    assert!(Origin::lua_global_stub("Origin").contains("---@type Origin\n"));
+    assert!(Player::lua_global_stub("Player").contains("---@field MAX_NAME any\n"));
+    Ok(())
+}
@@ -0,0 +1,24 @@
+-- `Display` wins over `Debug`
+local position = Position { x = 3, y = 4 }
+assert(tostring(position) == "(3, 4)")
+
+-- `Debug` when there is no `Display`
+assert(string.find(tostring(player), 'Player { name: "Ferris"', 1, true))
+
+-- Neither: mlua's default, whose `__name` is ignored by `tostring` before Lua 5.3
+if _VERSION ~= "Lua 5.1" and _VERSION ~= "Lua 5.2" then
+    assert(string.find(tostring(Opaque { id = 1 }), "^Opaque"))
+end
+
+-- Proxies resolve the value they point at
+local pos = player.position
+assert(tostring(pos) == "(1, 2)")
+pos.x = 7
+assert(tostring(pos) == "(7, 2)")
+
+assert(tostring(player.path) == "[Position(0, 0), Position(4, 5)]")
+assert(tostring(player.path[2]) == "(4, 5)")
+assert(tostring(player.scores) == "[10, 20]")
+
+-- Variant proxies print the whole enum
+assert(tostring(player.state.walking) == "Walking { speed: 3 }")
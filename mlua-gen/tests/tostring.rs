@@ -0,0 +1,68 @@
+use {
+    mlua_gen::{LuaBuilder, mlua_gen},
+    std::{
+        fmt,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Clone)]
+#[mlua_gen]
+struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl fmt::Debug for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Position({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+#[allow(dead_code)] // variants are reachable through Lua, not Rust
+enum State {
+    Idle,
+    Walking { speed: u32 },
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen]
+struct Player {
+    pub name:     String,
+    pub position: Position,
+    pub state:    State,
+    pub path:     Vec<Position>,
+    pub scores:   Vec<u32>,
+}
+
+#[mlua_gen]
+struct Opaque {
+    pub id: u32,
+}
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    Position::to_globals(&lua)?;
+    Opaque::to_globals(&lua)?;
+
+    let player = Arc::new(Mutex::new(Player {
+        name:     "Ferris".to_owned(),
+        position: Position { x: 1, y: 2 },
+        state:    State::Walking { speed: 3 },
+        path:     vec![Position { x: 0, y: 0 }, Position { x: 4, y: 5 }],
+        scores:   vec![10, 20],
+    }));
+    lua.globals().set("player", player.clone())?;
+
+    lua.load(include_str!("./tostring.lua")).exec()?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+local function rejected(f, message)
+    local ok, err = pcall(f)
+    assert(not ok)
+    assert(string.find(tostring(err), message, 1, true), tostring(err))
+end
+
+-- Top level fields
+rejected(function() player.hp = -5 end, "hp can't be negative")
+assert(player.hp == 10)
+player.hp = 0
+assert(player.hp == 0)
+
+-- Nested fields, through proxies
+rejected(function() player.stats.percent = 101 end, "tkr: percent out of range")
+assert(player.stats.percent == 50)
+
+-- Only the hooks of the root run: `check_stats` checks `Stats` userdata, not the `Stats`
+-- written through `player`, which `check_player` has to check
+rejected(function() stats.percent = 42 end, "42 is reserved")
+assert(stats.percent == 0)
+player.stats.percent = 42
+assert(player.stats.percent == 42)
+
+player.stats.percent = 100
+assert(player.stats.percent == 100)
+
+-- Tuple structs
+rejected(function() range[1] = 11 end, "expected a value in 0..=10")
+assert(range[1] == 0)
+range[1] = 5
+assert(range[1] == 5)
@@ -0,0 +1,73 @@
+use {
+    mlua::Value,
+    mlua_gen::{PathStep, mlua_gen},
+    std::sync::{Arc, Mutex},
+};
+
+fn check_player(player: &Player, steps: &[PathStep], value: &Value) -> mlua::Result<()> {
+    match steps {
+        [PathStep::Field("hp"), ..] if value.as_i64().is_some_and(|hp| hp < 0) => {
+            Err(mlua::Error::runtime("hp can't be negative"))
+        },
+        [
+            PathStep::Field("stats"),
+            PathStep::Field("percent"),
+        ] if value.as_i64().is_some_and(|percent| percent > 100) => {
+            Err(mlua::Error::runtime(format!(
+                "{}: percent out of range",
+                player.name
+            )))
+        },
+        _ => Ok(()),
+    }
+}
+
+fn check_stats(_: &Stats, steps: &[PathStep], value: &Value) -> mlua::Result<()> {
+    match (steps, value.as_i64()) {
+        ([PathStep::Field("percent")], Some(42)) => Err(mlua::Error::runtime("42 is reserved")),
+        _ => Ok(()),
+    }
+}
+
+fn check_range(_: &Range, steps: &[PathStep], value: &Value) -> mlua::Result<()> {
+    match (steps, value.as_i64()) {
+        ([PathStep::Tuple(_)], Some(0..=10)) => Ok(()),
+        _ => Err(mlua::Error::runtime("expected a value in 0..=10")),
+    }
+}
+
+#[derive(Debug, Clone)]
+#[mlua_gen(validate = crate::check_stats)]
+struct Stats {
+    pub percent: u8,
+}
+
+#[mlua_gen(validate = crate::check_player)]
+struct Player {
+    pub name:  String,
+    pub hp:    i32,
+    pub stats: Stats,
+}
+
+#[mlua_gen(validate = crate::check_range)]
+struct Range(pub i32, pub i32);
+
+#[test]
+pub fn test() -> mlua::Result<()> {
+    let lua = mlua::Lua::new();
+    let player = Arc::new(Mutex::new(Player {
+        name:  "tkr".to_owned(),
+        hp:    10,
+        stats: Stats { percent: 50 },
+    }));
+    lua.globals().set("player", player.clone())?;
+    lua.globals().set("stats", Stats { percent: 0 })?;
+    lua.globals().set("range", Range(0, 10))?;
+
+    lua.load(include_str!("./validate.lua")).exec()?;
+
+    let player = player.lock().expect("not poisoned");
+    assert_eq!(player.hp, 0);
+    assert_eq!(player.stats.percent, 100);
+    Ok(())
+}